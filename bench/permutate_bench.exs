@@ -0,0 +1,51 @@
+# Generation and classification throughput over long domains.
+#
+#     mix run bench/permutate_bench.exs
+#
+# Each kind is also timed on its own, since the character-level kinds
+# (Insertion, Replacement, Bitsquatting, ...) scale with label length.
+# VowelShuffle grows with the number of vowels instead, up to 15,624
+# candidates for six of them, and is capped by `:vowel_shuffle_budget`; it
+# is timed with the default budget and with one large enough to keep every
+# candidate.
+
+alias DomainTwistex.Permutate
+
+inputs = %{
+  "short (example.com)" => "example.com",
+  "long (30 chars)" => "internationalbusinessmachines.com",
+  "hyphenated, multi-label suffix" => "first-national-savings-and-loan.co.uk",
+  "max label (63 chars)" => String.duplicate("abcdefghi", 7) <> ".com"
+}
+
+kinds =
+  ~w(Addition Bitsquatting Hyphenation Insertion Omission Repetition Replacement Subdomain
+     Transposition VowelSwap DoubleVowelInsertion Homoglyph Mapped Morphological Phonetic)
+
+kind_jobs =
+  for kind <- kinds, into: %{} do
+    {"generate #{kind}", fn domain -> Permutate.generate_permutations(domain, only: [kind]) end}
+  end
+
+vowel_shuffle_jobs = %{
+  "generate VowelShuffle" => fn domain -> Permutate.generate_permutations(domain, only: ["VowelShuffle"]) end,
+  "generate VowelShuffle (budget 15,624)" => fn domain ->
+    Permutate.generate_permutations(domain, only: ["VowelShuffle"], vowel_shuffle_budget: 15_624)
+  end
+}
+
+Benchee.run(
+  kind_jobs
+  |> Map.merge(vowel_shuffle_jobs)
+  |> Map.merge(%{
+    "generate (defaults)" => &Permutate.generate_permutations/1,
+    "classify (omission)" => fn domain ->
+      {first, rest} = String.split_at(domain, 1)
+      Permutate.classify(first <> String.slice(rest, 1..-1//1), domain)
+    end
+  }),
+  inputs: inputs,
+  time: 3,
+  memory_time: 1,
+  print: [fast_warning: false]
+)
@@ -0,0 +1,136 @@
+defmodule DomainTwistex.Permutate.Confusables do
+  @moduledoc """
+  Unicode confusable detection based on UTS #39 (Unicode Security Mechanisms).
+
+  Mappings are read from `priv/confusables.txt` at compile time and used for:
+
+    * `skeleton/1` - the confusable skeleton of a domain; two names with the
+      same skeleton look alike (`pаypal.com` with a Cyrillic `а` and
+      `paypal.com`)
+    * `lookalikes/1` - the Unicode characters that render like an ASCII letter
+      or digit, used by the Homoglyph and Cyrillic generators
+
+  To refresh the data file, run:
+
+      mix update_confusables
+
+  """
+
+  @external_resource confusables_path =
+                       Path.join(:code.priv_dir(:domaintwistex), "confusables.txt")
+
+  @mappings confusables_path
+            |> File.read!()
+            |> String.split("\n")
+            |> Enum.map(&(&1 |> String.split("#", parts: 2) |> hd() |> String.trim()))
+            |> Enum.reject(&(&1 == ""))
+            |> Map.new(fn line ->
+              [source, target | _type] = line |> String.split(";") |> Enum.map(&String.trim/1)
+              decode = fn hex -> hex |> String.split() |> Enum.map(&String.to_integer(&1, 16)) end
+              {hd(decode.(source)), decode.(target)}
+            end)
+
+  # Source code point => prototype string. Targets are mapped again until
+  # nothing changes, so a single pass over the input produces the skeleton
+  # whichever copy of the file is bundled.
+  @table Map.new(@mappings, fn {source, target} ->
+           prototype =
+             Enum.reduce_while(1..10, target, fn _pass, chars ->
+               case Enum.flat_map(chars, &Map.get(@mappings, &1, [&1])) do
+                 ^chars -> {:halt, chars}
+                 mapped -> {:cont, mapped}
+               end
+             end)
+
+           {source, List.to_string(prototype)}
+         end)
+
+  # ASCII letter or digit => code points sharing its prototype. Only lowercase
+  # characters that survive IDNA mapping unchanged are kept; fullwidth and
+  # mathematical forms would normalise back to the ASCII original.
+  @lookalikes (for c <- Enum.concat(?a..?z, ?0..?9), into: %{} do
+                 prototype = Map.get(@table, c, <<c>>)
+
+                 glyphs =
+                   for {source, ^prototype} <- @table,
+                       source >= 0x80,
+                       glyph = <<source::utf8>>,
+                       String.downcase(glyph) == glyph,
+                       :unicode.characters_to_nfkc_binary(glyph) == glyph do
+                     source
+                   end
+
+                 {c, Enum.sort(glyphs)}
+               end)
+
+  @doc """
+  Computes the confusable skeleton of a domain.
+
+  Follows the UTS #39 skeleton algorithm (NFD, map every code point to its
+  prototype, NFD again), with two adjustments for domain names: punycode
+  labels are decoded first, and the input and result are lowercased, since
+  DNS is case-insensitive (`g00gle` and `google` share a skeleton even
+  though `0` maps to `O`).
+
+  ## Parameters
+    - domain: Domain name in Unicode or punycode form
+
+  ## Returns
+    - The skeleton string. It is only meant for comparison and is not
+      necessarily readable (`m` maps to `rn`).
+
+  ## Examples
+
+      iex> DomainTwistex.Permutate.Confusables.skeleton("pаypаl.net")
+      "paypal.net"
+
+      iex> DomainTwistex.Permutate.Confusables.skeleton("xn--ggle-55da.com") ==
+      ...>   DomainTwistex.Permutate.Confusables.skeleton("google.com")
+      true
+  """
+  def skeleton(domain) do
+    domain
+    |> display_form()
+    |> String.downcase()
+    |> :unicode.characters_to_nfd_binary()
+    |> String.to_charlist()
+    |> Enum.map_join(&Map.get(@table, &1, <<&1::utf8>>))
+    |> :unicode.characters_to_nfd_binary()
+    |> String.downcase()
+  end
+
+  @doc """
+  Returns true when two domains have the same skeleton.
+
+  ## Examples
+
+      iex> DomainTwistex.Permutate.Confusables.confusable?("paypa1.com", "paypal.com")
+      true
+
+      iex> DomainTwistex.Permutate.Confusables.confusable?("example.com", "examp1e.org")
+      false
+  """
+  def confusable?(domain, other), do: skeleton(domain) == skeleton(other)
+
+  @doc """
+  Returns the Unicode code points that look like an ASCII letter or digit.
+
+  ## Examples
+
+      iex> 0x0430 in DomainTwistex.Permutate.Confusables.lookalikes(?a)
+      true
+  """
+  def lookalikes(char) when is_integer(char), do: Map.get(@lookalikes, char, [])
+
+  @doc """
+  Returns the look-alike table for all ASCII letters and digits.
+  """
+  def lookalikes, do: @lookalikes
+
+  defp display_form(domain) do
+    case DomainTwistex.Permutate.IDNA.to_unicode(domain) do
+      {:ok, unicode} -> unicode
+      {:error, _reason} -> domain
+    end
+  end
+end
@@ -0,0 +1,279 @@
+defmodule DomainTwistex.Permutate.Data do
+  @moduledoc """
+  Lookup tables shared by the permutation generators: vowels, homoglyphs,
+  character mappings, word-level (morphological) substitutions, phonetic
+  spellings, TLDs and keywords. Keyboard layouts live in
+  `DomainTwistex.Permutate.Keyboard`.
+
+  TLDs and keywords are compiled in from `priv/tlds.txt` and
+  `priv/keywords.txt`. Either can be replaced at runtime without recompiling,
+  by pointing the application environment at another file (one entry per
+  line, `#` comments allowed):
+
+      config :domaintwistex,
+        tlds_path: "/var/lib/domaintwistex/tlds.txt",
+        keywords_path: "/var/lib/domaintwistex/keywords.txt"
+
+  The file is read on first use and cached; call `reload/0` after changing
+  it. When the file is missing, unreadable or empty the compiled-in list is
+  used and a warning logged. `mix update_tlds` rebuilds the TLD list. Keyword
+  packs live in `DomainTwistex.Permutate.Keywords`.
+  """
+
+  require Logger
+
+  @vowels [?a, ?e, ?i, ?o, ?u, ?A, ?E, ?I, ?O, ?U]
+  @vowel_shuffle_ceiling 6
+  @ascii_lower [?a, ?b, ?c, ?d, ?e, ?f, ?g, ?h, ?i, ?j, ?k, ?l, ?m, ?n, ?o, ?p, ?q, ?r, ?s, ?t, ?u, ?v, ?w, ?x, ?y, ?z]
+
+  @homoglyphs %{
+    ?a => ~C"àáâãäåɑạǎăȧą",
+    ?b => ~C"dʙɓḃḅḇƅ",
+    ?c => ~C"eƈċćçčĉo",
+    ?d => ~C"bɗđďɖḑḋḍḏḓ",
+    ?e => ~C"céèêëēĕěėẹęȩɇḛ",
+    ?f => ~C"ƒḟ",
+    ?g => ~C"qɢɡġğǵģĝǧǥ",
+    ?h => ~C"ĥȟħɦḧḩⱨḣḥḫẖ",
+    ?i => ~C"1líìïıɩǐĭỉịɨȋī",
+    ?j => ~C"ʝɉ",
+    ?k => ~C"ḳḵⱪķ",
+    ?l => ~C"1iɫł",
+    ?m => ~C"nṁṃᴍɱḿ",
+    ?n => ~C"mrńṅṇṉñņǹňꞑ",
+    ?o => ~C"0ȯọỏơóö",
+    ?p => ~C"ƿƥṕṗ",
+    ?q => ~C"gʠ",
+    ?r => ~C"ʀɼɽŕŗřɍɾȓȑṙṛṟ",
+    ?s => ~C"ʂśṣṡșŝš",
+    ?t => ~C"ţŧṫṭțƫ",
+    ?u => ~C"ᴜǔŭüʉùúûũūųưůűȕȗụ",
+    ?v => ~C"ṿⱱᶌṽⱴ",
+    ?w => ~C"ŵẁẃẅⱳẇẉẘ",
+    ?y => ~C"ʏýÿŷƴȳɏỿẏỵ",
+    ?z => ~C"ʐżźᴢƶẓẕⱬ"
+  }
+
+  @mapped %{
+    "a" => ["4"], "b" => ["8", "6"], "c" => [], "d" => ["cl"],
+    "e" => ["3"], "f" => ["ph"], "g" => ["9", "6"], "h" => [],
+    "i" => ["1", "l"], "j" => [], "k" => [], "l" => ["1", "i"],
+    "m" => ["rn", "nn"], "n" => [], "o" => ["0"], "p" => [],
+    "q" => ["9"], "r" => [], "s" => ["5", "z"], "t" => ["7"],
+    "u" => ["v"], "v" => ["u"], "w" => ["vv"], "x" => [],
+    "y" => [], "z" => ["2", "s"], "0" => ["o"], "1" => ["i", "l"],
+    "2" => ["z"], "3" => ["e"], "4" => ["a"], "5" => ["s"],
+    "6" => ["b", "g"], "7" => ["t"], "8" => ["b"], "9" => ["g", "q"],
+    "ck" => ["kk"], "oo" => ["00"]
+  }
+
+  # Word-level substitutions for the Morphological generator. Unlike
+  # @mapped, they only apply at word boundaries (label edges, hyphens and
+  # letter/digit changes): number words must make up a whole word, alone or
+  # run together (`one`, `onetwothree`), and inflections apply to the end of
+  # a word. Number words list the usual spelling of each digit first.
+  @number_words [
+    {"zero", "0"}, {"one", "1"}, {"two", "2"}, {"three", "3"}, {"four", "4"},
+    {"five", "5"}, {"six", "6"}, {"seven", "7"}, {"eight", "8"}, {"nine", "9"},
+    {"ten", "10"}, {"to", "2"}, {"too", "2"}, {"for", "4"}
+  ]
+
+  # English word endings and their plural or singular forms, most specific
+  # first; the first ending that matches applies
+  @inflections [
+    {"ies", ["y", "ie"]}, {"ves", ["f", "fe", "ve"]}, {"sses", ["ss"]}, {"ches", ["ch", "che"]},
+    {"shes", ["sh"]}, {"xes", ["x"]}, {"zes", ["z", "ze"]}, {"ss", ["sses"]}, {"us", ["uses"]},
+    {"s", [""]}, {"ch", ["ches"]}, {"sh", ["shes"]}, {"x", ["xes"]}, {"z", ["zes"]},
+    {"fe", ["fes", "ves"]}, {"ff", ["ffs"]}, {"f", ["fs", "ves"]}, {"ay", ["ays"]},
+    {"ey", ["eys"]}, {"oy", ["oys"]}, {"uy", ["uys"]}, {"y", ["ies"]}, {"", ["s"]}
+  ]
+
+  # Spellings that sound alike in English, used by the Phonetic generator.
+  # Candidates are only kept when their Double Metaphone key matches.
+  @phonetic_rules %{
+    "ph" => ["f"], "f" => ["ph"], "gh" => ["f"], "qu" => ["kw"],
+    "kw" => ["qu"], "ck" => ["k", "c"], "k" => ["c", "ck"], "c" => ["k", "s"],
+    "s" => ["c", "z"], "z" => ["s"], "x" => ["ks", "cks"], "ks" => ["x"],
+    "igh" => ["i", "y"], "ight" => ["ite"], "ite" => ["ight"], "ee" => ["ea", "ie"],
+    "ea" => ["ee"], "oo" => ["u", "ew"], "ew" => ["oo"], "wr" => ["r"],
+    "kn" => ["n"], "wh" => ["w"], "y" => ["i", "ie"], "ie" => ["y"],
+    "ou" => ["ow"], "ow" => ["ou", "o"], "tion" => ["shun", "sion"], "sion" => ["tion"],
+    "er" => ["or", "ur"], "or" => ["er"], "ai" => ["ay"], "ay" => ["ai", "ey"],
+    "j" => ["g"], "ge" => ["je"], "ch" => ["tch"], "tch" => ["ch"]
+  }
+
+  # Suffixes mistaken for one another, in both directions (TldTypo)
+  @confusable_tlds [
+    {"com", "co"}, {"com", "cm"}, {"com", "om"}, {"com", "con"}, {"com", "corn"},
+    {"com", "cam"}, {"com", "comm"}, {"net", "ne"}, {"net", "nt"}, {"net", "nett"},
+    {"org", "orq"}, {"org", "or"}, {"org", "ogr"}, {"co.uk", "co"}, {"co.uk", "uk"},
+    {"co.uk", "com"}, {"com.au", "com"}, {"com.au", "au"}, {"de", "dk"}, {"info", "inf"},
+    {"io", "lo"}, {"ai", "al"}
+  ]
+
+  # TLDs tried after the original suffix has been folded into the label
+  # (`examplecom.net`, `example-com.org`)
+  @popular_tlds ~w(com net org co io info biz)
+
+  # How often each kind shows up among registered look-alikes, as a prior
+  # for ranking candidates before resolution (see Likelihood). Kinds not
+  # listed, such as custom ones, get 0.5.
+  @kind_base_rates %{
+    "Homoglyph" => 0.9, "Cyrillic" => 0.85, "Omission" => 0.8, "Transposition" => 0.8,
+    "TldTypo" => 0.75, "Replacement" => 0.75, "Repetition" => 0.7, "Insertion" => 0.7,
+    "Mapped" => 0.65, "Various" => 0.65, "Keyword" => 0.6, "Morphological" => 0.6,
+    "Hyphenation" => 0.55, "Tld" => 0.5, "Addition" => 0.5, "VowelSwap" => 0.5,
+    "Phonetic" => 0.5, "Subdomain" => 0.4, "LayoutSwitch" => 0.35,
+    "DoubleVowelInsertion" => 0.35, "Bitsquatting" => 0.3, "VowelShuffle" => 0.25,
+    "FauxTld" => 0.2
+  }
+
+  # Relative popularity of suffixes with registrants and typing users, for
+  # ranking; unlisted suffixes get 0.2 and the input's own suffix 1.0
+  @tld_popularity %{
+    "com" => 1.0, "net" => 0.8, "org" => 0.75, "co" => 0.7, "io" => 0.6, "cm" => 0.55,
+    "info" => 0.5, "om" => 0.5, "co.uk" => 0.5, "uk" => 0.5, "de" => 0.5, "biz" => 0.45,
+    "us" => 0.45, "app" => 0.45, "online" => 0.45, "xyz" => 0.45, "top" => 0.45,
+    "site" => 0.4, "shop" => 0.4, "ru" => 0.4, "cn" => 0.4, "me" => 0.4, "ai" => 0.4
+  }
+
+  # Categories of the default keywords, by the kind of lure they make.
+  # Keywords not listed are "generic".
+  @keyword_categories %{
+    "credential" => ~w(2fa account accounts acct activate auth2 authn authz credential confirm
+                       confirmation forgot id identity kyc login logon mfa password pin protect
+                       pw pwd relogin reset resetpw saml sec secure securemail session settings
+                       verify verification unlock update validate),
+    "support" => ~w(assist care contact contactus customer customercare customerservice faq help
+                    helpdesk helpme inquiry issue servicedesk support ticket),
+    "payment" => ~w(bank banking bill billing charges checkout checkoutcart checkoutnow credit deposit
+                    fee fees invoice pay paybill payee paylater payment payments paynow payout
+                    qrpay receipt refund refunds wallet),
+    "shipping" => ~w(delivery deliverydate deliveryinfo deliveryoptions deliverystatus dispatch parcel
+                     pickup pickuplocation pickuppoint pickups returnlabel shipment shipme tracking),
+    "promo" => ~w(claimoffer claims coupon coupons deal deals discount discounts flashsale free
+                  giftcard giftcards offers prize prizes promo promocode promocodes redeem)
+  }
+
+  @external_resource tlds_path = Path.join([:code.priv_dir(:domaintwistex), "tlds.txt"])
+  @external_resource keywords_path = Path.join([:code.priv_dir(:domaintwistex), "keywords.txt"])
+
+  @tlds File.read!(tlds_path)
+        |> String.split("\n", trim: true)
+  @keywords File.read!(keywords_path)
+             |> String.split("\n", trim: true)
+
+  @doc "Vowels (both cases) used by the vowel generators."
+  def vowels, do: @vowels
+
+  @doc "Maximum number of vowel positions VowelShuffle permutes."
+  def vowel_shuffle_ceiling, do: @vowel_shuffle_ceiling
+
+  @doc "Lowercase ASCII letters as code points."
+  def ascii_lower, do: @ascii_lower
+
+  @doc "Unicode look-alikes for each ASCII letter, as charlists."
+  def homoglyphs, do: @homoglyphs
+
+  @doc "Single and multi-character visual substitutions (`m` => `rn`, ...)."
+  def mapped, do: @mapped
+
+  @doc "Number words and the digits they stand for, usual spelling first (`one` => `1`, `for` => `4`)."
+  def number_words, do: @number_words
+
+  @doc "English word endings and their plural or singular forms (`y` => `ies`, `ies` => `y`)."
+  def inflections, do: @inflections
+
+  @doc "Grapheme substitutions (`ph` => `f`, `qu` => `kw`, ...) tried by the Phonetic generator."
+  def phonetic_rules, do: @phonetic_rules
+
+  @doc """
+  Known TLDs and public suffixes, from the `:tlds_path` file when
+  configured, otherwise compiled in.
+  """
+  def tlds, do: runtime(:tlds_path, :list, @tlds)
+
+  @doc "`tlds/0` as a `MapSet`, for membership checks."
+  def tld_set, do: runtime(:tlds_path, :set, @tlds)
+
+  @doc """
+  Common phishing keywords, from the `:keywords_path` file when configured,
+  otherwise compiled in.
+  """
+  def keywords, do: runtime(:keywords_path, :list, @keywords)
+
+  @doc """
+  Drops the cached runtime TLD and keyword lists, so that the configured
+  files are read again on next use.
+  """
+  def reload do
+    for {{__MODULE__, _, _} = key, _} <- :persistent_term.get(), do: :persistent_term.erase(key)
+    :ok
+  end
+
+  @doc "Pairs of suffixes commonly mistaken for one another."
+  def confusable_tlds, do: @confusable_tlds
+
+  @doc "Popular TLDs for candidates that fold the original suffix into the label."
+  def popular_tlds, do: @popular_tlds
+
+  @doc "Prior rate of each kind among registered look-alikes, between 0 and 1."
+  def kind_base_rates, do: @kind_base_rates
+
+  @doc "Relative popularity of common suffixes, between 0 and 1."
+  def tld_popularity, do: @tld_popularity
+
+  @doc "Default keywords by category (credential, support, payment, shipping, promo)."
+  def keyword_categories, do: @keyword_categories
+
+  # The configured file's entries, cached in :persistent_term by path so
+  # that hot paths (classification runs per candidate) stay cheap
+  defp runtime(env_key, form, fallback) do
+    case Application.get_env(:domaintwistex, env_key) do
+      nil when form == :list ->
+        fallback
+
+      nil ->
+        cached({__MODULE__, env_key, :compiled_set}, fn -> MapSet.new(fallback) end)
+
+      path ->
+        list = cached({__MODULE__, env_key, path}, fn -> read_list(path, env_key, fallback) end)
+
+        case form do
+          :list -> list
+          :set -> cached({__MODULE__, env_key, {:set, path}}, fn -> MapSet.new(list) end)
+        end
+    end
+  end
+
+  defp cached(key, fun) do
+    case :persistent_term.get(key, nil) do
+      nil ->
+        value = fun.()
+        :persistent_term.put(key, value)
+        value
+
+      value ->
+        value
+    end
+  end
+
+  defp read_list(path, env_key, fallback) do
+    with {:ok, contents} <- File.read(path),
+         [_ | _] = entries <- parse_list(contents) do
+      entries
+    else
+      error ->
+        reason = if error == [], do: "no entries", else: :file.format_error(elem(error, 1))
+        Logger.warning("Cannot load #{inspect(env_key)} from #{path} (#{reason}), using the compiled-in list")
+        fallback
+    end
+  end
+
+  defp parse_list(contents) do
+    for line <- String.split(contents, ~r/\R/),
+        line = line |> String.trim() |> String.downcase(),
+        line != "" and not String.starts_with?(line, "#"),
+        do: line
+  end
+end
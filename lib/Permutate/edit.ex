@@ -0,0 +1,85 @@
+defmodule DomainTwistex.Permutate.Edit do
+  @moduledoc """
+  Provenance of a permutation: the edit a generator applied to the input.
+
+  Generators store it under the `:edit` key of each candidate, and
+  `DomainTwistex.Permutate.classify/3` returns the same maps. Which fields
+  are present depends on the kind:
+
+    * `:position` - index in the registrable label where the edit applies
+      (code points, 0-based)
+    * `:positions` - changed indexes, for edits touching several (VowelShuffle)
+    * `:original` - the substring that was replaced or removed (`""` for insertions)
+    * `:replacement` - the substring put in its place (`""` for omissions)
+    * `:layout` - keyboard layout whose neighbouring key was used (`:qwerty`, ...)
+    * `:keyword` - keyword added, with `:placement` (`:prefix` or `:suffix`),
+      `:separator` and `:category` (see `DomainTwistex.Permutate.Keywords`)
+    * `:bit` - for Bitsquatting, the bit (0-7) flipped in the `:original`
+      character
+    * `:rule` - for Morphological, `:plural`, `:singular`, `:word_to_digit`
+      or `:digit_to_word`
+    * `:pattern` - for Various, the dot that was dropped (`:service_prefix`,
+      `:suffix` or `:subdomain`)
+    * `:typo` - for TldTypo, the typo made in the suffix (`:omission`,
+      `:transposition`, `:substitution`, `:confusable` or `:dot`), with the
+      new `:suffix` for `:dot`
+    * `:intended_layout` and `:active_layout` - for LayoutSwitch, the layout
+      the label is written for and the one that was active when typing it
+
+  Depth-2 candidates carry a list of two edits, one per kind in the chain.
+  """
+
+  @type t :: %{optional(atom()) => term()}
+
+  @doc """
+  Describes an edit, or a chain of edits, in plain words.
+
+  ## Examples
+
+      iex> DomainTwistex.Permutate.Edit.describe(%{position: 2, original: "w", replacement: "q", layout: :qwerty})
+      "`q` substituted for `w` at index 2 (QWERTY neighbour)"
+
+      iex> DomainTwistex.Permutate.Edit.describe(%{keyword: "login", placement: :suffix, separator: "-"})
+      "keyword `login` appended"
+  """
+  def describe(edits) when is_list(edits) do
+    edits
+    |> Enum.map(&describe/1)
+    |> Enum.join(", then ")
+  end
+
+  def describe(%{keyword: keyword, placement: :prefix}), do: "keyword `#{keyword}` prepended"
+  def describe(%{keyword: keyword, placement: :suffix}), do: "keyword `#{keyword}` appended"
+
+  def describe(%{positions: positions, original: original, replacement: replacement}) do
+    "`#{original}` changed to `#{replacement}` at indexes #{Enum.join(positions, ", ")}"
+  end
+
+  def describe(%{position: position, original: "", replacement: replacement} = edit) do
+    "`#{replacement}` inserted at index #{position}" <> note(edit)
+  end
+
+  def describe(%{position: position, original: original, replacement: ""}) do
+    "`#{original}` omitted at index #{position}"
+  end
+
+  def describe(%{position: position, original: original, replacement: replacement} = edit) do
+    "`#{replacement}` substituted for `#{original}` at index #{position}" <> note(edit)
+  end
+
+  def describe(%{original: original, replacement: replacement, intended_layout: intended, active_layout: active}) do
+    "`#{original}` typed as `#{replacement}` (#{layout_name(intended)} text on #{layout_name(active)} layout)"
+  end
+
+  def describe(%{original: original, replacement: replacement}) do
+    "`#{original}` replaced with `#{replacement}`"
+  end
+
+  def describe(_edit), do: "unknown edit"
+
+  defp note(%{layout: layout}), do: " (#{layout_name(layout)} neighbour)"
+  defp note(%{bit: bit}), do: " (bit #{bit} flipped)"
+  defp note(_edit), do: ""
+
+  defp layout_name(layout), do: layout |> Atom.to_string() |> String.upcase()
+end
@@ -3,439 +3,747 @@ defmodule DomainTwistex.Permutate do
   Pure Elixir domain permutation generator.
 
   Drop-in replacement for the Rust NIF `DomainTwistex.Utils.generate_permutations/1`.
-  Generates all 18 permutation types matching twistrs.
+  Generates the 18 permutation types of twistrs plus Cyrillic, Phonetic,
+  LayoutSwitch, TldTypo, Various and Morphological.
 
   Addition, Bitsquatting, Hyphenation, HyphenationTldBoundary, Insertion,
   Omission, Repetition, Replacement, Subdomain, Transposition, VowelSwap,
-  VowelShuffle, DoubleVowelInsertion, Keyword, Tld, FauxTld, Mapped, Homoglyph
+  VowelShuffle, DoubleVowelInsertion, Keyword, Tld, FauxTld, Mapped, Homoglyph,
+  Cyrillic, Phonetic, LayoutSwitch, TldTypo, Various, Morphological
+
+  Each kind is a module implementing `DomainTwistex.Permutate.Generator`,
+  e.g. `DomainTwistex.Permutate.Generators.Homoglyph`. Additional kinds can be
+  registered through application config or the `:generators` option.
+
+  Candidates that are not valid host names (see
+  `DomainTwistex.Permutate.Hostname`) are dropped before they are returned.
   """
 
-  @vowels [?a, ?e, ?i, ?o, ?u, ?A, ?E, ?I, ?O, ?U]
-  @vowel_shuffle_ceiling 6
-  @ascii_lower [?a, ?b, ?c, ?d, ?e, ?f, ?g, ?h, ?i, ?j, ?k, ?l, ?m, ?n, ?o, ?p, ?q, ?r, ?s, ?t, ?u, ?v, ?w, ?x, ?y, ?z]
-
-  @homoglyphs %{
-    ?a => ~C"àáâãäåɑạǎăȧą",
-    ?b => ~C"dʙɓḃḅḇƅ",
-    ?c => ~C"eƈċćçčĉo",
-    ?d => ~C"bɗđďɖḑḋḍḏḓ",
-    ?e => ~C"céèêëēĕěėẹęȩɇḛ",
-    ?f => ~C"ƒḟ",
-    ?g => ~C"qɢɡġğǵģĝǧǥ",
-    ?h => ~C"ĥȟħɦḧḩⱨḣḥḫẖ",
-    ?i => ~C"1líìïıɩǐĭỉịɨȋī",
-    ?j => ~C"ʝɉ",
-    ?k => ~C"ḳḵⱪķ",
-    ?l => ~C"1iɫł",
-    ?m => ~C"nṁṃᴍɱḿ",
-    ?n => ~C"mrńṅṇṉñņǹňꞑ",
-    ?o => ~C"0ȯọỏơóö",
-    ?p => ~C"ƿƥṕṗ",
-    ?q => ~C"gʠ",
-    ?r => ~C"ʀɼɽŕŗřɍɾȓȑṙṛṟ",
-    ?s => ~C"ʂśṣṡșŝš",
-    ?t => ~C"ţŧṫṭțƫ",
-    ?u => ~C"ᴜǔŭüʉùúûũūųưůűȕȗụ",
-    ?v => ~C"ṿⱱᶌṽⱴ",
-    ?w => ~C"ŵẁẃẅⱳẇẉẘ",
-    ?y => ~C"ʏýÿŷƴȳɏỿẏỵ",
-    ?z => ~C"ʐżźᴢƶẓẕⱬ"
-  }
-
-  @mapped %{
-    "a" => ["4"], "b" => ["8", "6"], "c" => [], "d" => ["cl"],
-    "e" => ["3"], "f" => ["ph"], "g" => ["9", "6"], "h" => [],
-    "i" => ["1", "l"], "j" => [], "k" => [], "l" => ["1", "i"],
-    "m" => ["rn", "nn"], "n" => [], "o" => ["0"], "p" => [],
-    "q" => ["9"], "r" => [], "s" => ["5", "z"], "t" => ["7"],
-    "u" => ["v"], "v" => ["u"], "w" => ["vv"], "x" => [],
-    "y" => [], "z" => ["2", "s"], "0" => ["o"], "1" => ["i", "l"],
-    "2" => ["z"], "3" => ["e"], "4" => ["a"], "5" => ["s"],
-    "6" => ["b", "g"], "7" => ["t"], "8" => ["b"], "9" => ["g", "q"],
-    "ck" => ["kk"], "oo" => ["00"]
-  }
-
-  @keyboard_layouts [
-    %{?1 => "2q", ?2 => "3wq1", ?3 => "4ew2", ?4 => "5re3", ?5 => "6tr4",
-      ?6 => "7yt5", ?7 => "8uy6", ?8 => "9iu7", ?9 => "0oi8", ?0 => "po9",
-      ?q => "12wa", ?w => "3esaq2", ?e => "4rdsw3", ?r => "5tfde4",
-      ?t => "6ygfr5", ?y => "7uhgt6", ?u => "8ijhy7", ?i => "9okju8",
-      ?o => "0plki9", ?p => "lo0", ?a => "qwsz", ?s => "edxzaw",
-      ?d => "rfcxse", ?f => "tgvcdr", ?g => "yhbvft", ?h => "ujnbgy",
-      ?j => "ikmnhu", ?k => "olmji", ?l => "kop", ?z => "asx",
-      ?x => "zsdc", ?c => "xdfv", ?v => "cfgb", ?b => "vghn",
-      ?n => "bhjm", ?m => "njk"},
-    %{?1 => "2q", ?2 => "3wq1", ?3 => "4ew2", ?4 => "5re3", ?5 => "6tr4",
-      ?6 => "7zt5", ?7 => "8uz6", ?8 => "9iu7", ?9 => "0oi8", ?0 => "po9",
-      ?q => "12wa", ?w => "3esaq2", ?e => "4rdsw3", ?r => "5tfde4",
-      ?t => "6zgfr5", ?z => "7uhgt6", ?u => "8ijhz7", ?i => "9okju8",
-      ?o => "0plki9", ?p => "lo0", ?a => "qwsy", ?s => "edxyaw",
-      ?d => "rfcxse", ?f => "tgvcdr", ?g => "zhbvft", ?h => "ujnbgz",
-      ?j => "ikmnhu", ?k => "olmji", ?l => "kop", ?y => "asx",
-      ?x => "ysdc", ?c => "xdfv", ?v => "cfgb", ?b => "vghn",
-      ?n => "bhjm", ?m => "njk"},
-    %{?1 => "2a", ?2 => "3za1", ?3 => "4ez2", ?4 => "5re3", ?5 => "6tr4",
-      ?6 => "7yt5", ?7 => "8uy6", ?8 => "9iu7", ?9 => "0oi8", ?0 => "po9",
-      ?a => "2zq1", ?z => "3esqa2", ?e => "4rdsz3", ?r => "5tfde4",
-      ?t => "6ygfr5", ?y => "7uhgt6", ?u => "8ijhy7", ?i => "9okju8",
-      ?o => "0plki9", ?p => "lo0m", ?q => "zswa", ?s => "edxwqz",
-      ?d => "rfcxse", ?f => "tgvcdr", ?g => "yhbvft", ?h => "ujnbgy",
-      ?j => "iknhu", ?k => "olji", ?l => "kopm", ?m => "lp",
-      ?w => "sxq", ?x => "wsdc", ?c => "xdfv", ?v => "cfgb",
-      ?b => "vghn", ?n => "bhj"}
+  alias DomainTwistex.Permutate.Generator
+  alias DomainTwistex.Permutate.Generators
+  alias DomainTwistex.Permutate.Hostname
+  alias DomainTwistex.Permutate.IDNA
+  alias DomainTwistex.Permutate.PublicSuffix
+
+  @builtin_generators [
+    Generators.Addition,
+    Generators.Bitsquatting,
+    Generators.Hyphenation,
+    Generators.Insertion,
+    Generators.Omission,
+    Generators.Repetition,
+    Generators.Replacement,
+    Generators.Subdomain,
+    Generators.Various,
+    Generators.Transposition,
+    Generators.VowelSwap,
+    Generators.Keyword,
+    Generators.Tld,
+    Generators.TldTypo,
+    Generators.Mapped,
+    Generators.Morphological,
+    Generators.Homoglyph,
+    Generators.Cyrillic,
+    Generators.Phonetic,
+    Generators.LayoutSwitch,
+    Generators.VowelShuffle,
+    Generators.DoubleVowelInsertion,
+    Generators.FauxTld
+  ]
+
+  # Primary kind of a candidate several kinds produce, most specific first.
+  # Kinds not listed (custom generators) follow in run order.
+  @kind_priority [
+    "TldTypo",
+    "Tld",
+    "Keyword",
+    "Homoglyph",
+    "Cyrillic",
+    "LayoutSwitch",
+    "Mapped",
+    "Morphological",
+    "Transposition",
+    "Omission",
+    "Repetition",
+    "Replacement",
+    "Insertion",
+    "VowelSwap",
+    "Hyphenation",
+    "Subdomain",
+    "Various",
+    "Phonetic",
+    "Addition",
+    "DoubleVowelInsertion",
+    "VowelShuffle",
+    "Bitsquatting",
+    "FauxTld"
   ]
 
-  @tlds File.read!(Path.join([:code.priv_dir(:domaintwistex), "tlds.txt"]))
-        |> String.split("\n", trim: true)
-  @keywords File.read!(Path.join([:code.priv_dir(:domaintwistex), "keywords.txt"]))
-             |> String.split("\n", trim: true)
+  # Kind pairs combined at depth 2 by default, with a plausibility weight
+  # for ranking (the most common real-world combinations come first)
+  @default_pairs [
+    {"Homoglyph", "Tld", 1.0},
+    {"Keyword", "Tld", 0.95},
+    {"Transposition", "Tld", 0.9},
+    {"Omission", "Tld", 0.9},
+    {"Replacement", "Tld", 0.85},
+    {"Keyword", "Omission", 0.85},
+    {"Keyword", "Transposition", 0.85},
+    {"Keyword", "Homoglyph", 0.8},
+    {"Mapped", "Tld", 0.8},
+    {"Hyphenation", "Tld", 0.75},
+    {"Addition", "Tld", 0.7},
+    {"Repetition", "Tld", 0.7},
+    {"VowelSwap", "Tld", 0.7}
+  ]
+  @default_budget 1_000
+  @default_depth_seeds 10
 
   @doc """
   Generates all domain permutations for a given FQDN.
-  Returns a list of maps with :fqdn, :tld, and :kind keys,
-  matching the output format of the Rust NIF.
+  Returns a list of maps with :fqdn, :unicode, :tld, :kind, :kinds and :edit keys.
+
+  `:fqdn` and `:tld` are always in ASCII form, with internationalized labels
+  punycode-encoded (`xn--gle-rnaa8v.com`), so they can be passed straight to
+  DNS, WHOIS and RDAP. `:unicode` holds the display form (`ğöögle.com`) and
+  equals `:fqdn` for plain ASCII candidates. Candidates with labels that IDNA
+  rules forbid are dropped (see `DomainTwistex.Permutate.IDNA`).
+
+  The input is split with the Public Suffix List into subdomain, registrable
+  label and public suffix (see `DomainTwistex.Permutate.PublicSuffix`). Every
+  generator mutates the registrable label only, so `login.example.co.uk`
+  yields candidates such as `login.exmaple.co.uk` and `login.example.com`.
+  The `:tld` key of each result holds the candidate's public suffix.
+
+  `:edit` records what the generator changed, such as the position, original
+  and replacement characters, keyboard layout or keyword (see
+  `DomainTwistex.Permutate.Edit`). Custom generators may omit it.
+
+  ## Attribution
+
+  A domain produced by several kinds is emitted once, with every kind that
+  produces it in `:kinds` (`googie.com` may be both a Replacement and a
+  Mapped candidate). `:kind` is the primary one, chosen by a fixed priority
+  so that results are stable whatever the run order: TldTypo, Tld, Keyword,
+  Homoglyph, Cyrillic, LayoutSwitch, Mapped, Morphological, Transposition,
+  Omission, Repetition, Replacement, Insertion, VowelSwap, Hyphenation,
+  Subdomain, Various, Phonetic, Addition, DoubleVowelInsertion,
+  VowelShuffle, Bitsquatting, FauxTld, then custom kinds in run order.
+  `:edit` is the primary kind's edit.
+
+  Only kinds selected for the run are attributed, and other kinds are
+  detected with `c:DomainTwistex.Permutate.Generator.classify/3`, so custom
+  generators without it are only listed for the candidates they emit.
 
   Options:
     - `:faux_tld` - include FauxTld permutations (default: false, adds ~14K entries)
     - `:double_vowel` - include DoubleVowelInsertion (default: true)
     - `:vowel_shuffle` - include VowelShuffle (default: true)
+    - `:vowel_shuffle_budget` - most VowelShuffle candidates per domain
+      (default: 1000); larger products are sampled (see `skipped/2`)
+    - `:vowel_shuffle_seed` - seed of the VowelShuffle sample (default: 0)
+    - `:generators` - extra `DomainTwistex.Permutate.Generator` modules to run
+    - `:only` - list of kind names to generate, e.g. `["Homoglyph", "Tld", "Keyword"]`.
+      Listed kinds run even when disabled by default (FauxTld)
+    - `:except` - list of kind names to skip
+    - `:max_per_kind` - cap on unique candidates per (primary) kind, either an
+      integer applied to every kind or a map such as `%{"Tld" => 200}`
+    - `:depth` - 1 (default) or 2. Depth 2 appends candidates combining two
+      kinds, see "Depth 2" below
+    - `:budget` - maximum number of depth-2 candidates (default: 1000)
+    - `:pairs` - kind pairs applied at depth 2, as `{first, second}` or
+      `{first, second, weight}` tuples (default: common combinations such as
+      `{"Homoglyph", "Tld"}` and `{"Keyword", "Omission"}`)
+    - `:depth_seeds` - first-kind candidates expanded per pair (default: 10)
+    - `:layouts` - keyboard layouts used by Insertion and Replacement, as
+      names or `name: weight` pairs (default: `[:qwerty, :qwertz, :azerty]`,
+      see `DomainTwistex.Permutate.Keyboard`)
+
+  Unknown kind names in `:only`, `:except`, `:max_per_kind` or `:pairs` raise an
+  `ArgumentError`. Options are also passed to every generator, so custom generators can read
+  their own keys.
+
+  ## Depth 2
+
+  With `depth: 2`, each pair `{first, second}` applies the `second` kind to
+  the `:depth_seeds` most plausible candidates of the `first` kind, e.g. a
+  Homoglyph candidate moved to other TLDs. These candidates come after all
+  single-kind ones and carry the chain as their kind,
+  `kind: ["Keyword", "Omission"]` (and `kinds: [["Keyword", "Omission"]]`),
+  and one edit per kind as their `:edit`.
+
+  Depth-2 candidates are ranked by the pair weight times their similarity to
+  the input (Jaro distance of the name, and to a lesser degree of the
+  suffix). Only the `:budget` best are kept, best first. Candidates that
+  already appeared at depth 1 are skipped. `:only`, `:except` and
+  `:max_per_kind` apply to depth 1 only.
   """
   def generate_permutations(fqdn, opts \\ []) do
-    include_faux_tld = Keyword.get(opts, :faux_tld, false)
-    include_double_vowel = Keyword.get(opts, :double_vowel, true)
-    include_vowel_shuffle = Keyword.get(opts, :vowel_shuffle, true)
-
-    {domain, tld} = parse_domain(fqdn)
-
-    base =
-      addition(domain, tld)
-      |> Stream.concat(bitsquatting(fqdn))
-      |> Stream.concat(hyphenation(fqdn))
-      |> Stream.concat(hyphenation_tld_boundary(domain, tld))
-      |> Stream.concat(insertion(fqdn))
-      |> Stream.concat(omission(fqdn))
-      |> Stream.concat(repetition(fqdn))
-      |> Stream.concat(replacement(fqdn))
-      |> Stream.concat(subdomain(fqdn))
-      |> Stream.concat(transposition(fqdn))
-      |> Stream.concat(vowel_swap(fqdn))
-      |> Stream.concat(keyword(domain, tld))
-      |> Stream.concat(tld(domain))
-      |> Stream.concat(mapped(domain, tld))
-      |> Stream.concat(homoglyph(fqdn))
-
-    with_extras =
-      base
-      |> maybe_concat(include_vowel_shuffle, vowel_shuffle(domain, tld))
-      |> maybe_concat(include_double_vowel, double_vowel_insertion(fqdn))
-      |> maybe_concat(include_faux_tld, faux_tld(domain, tld))
-
-    with_extras
-    |> Enum.uniq_by(& &1.fqdn)
-    |> Enum.reject(&invalid_fqdn?/1)
+    fqdn
+    |> stream(opts)
+    |> Enum.to_list()
   end
 
-  defp invalid_fqdn?(%{fqdn: fqdn}) do
-    fqdn == "" or String.contains?(fqdn, "..") or String.starts_with?(fqdn, ".") or
-      String.ends_with?(fqdn, ".") or String.contains?(fqdn, "--")
-  end
+  @doc """
+  Lazily generates deduplicated domain permutations.
 
-  defp maybe_concat(stream, true, extras), do: Stream.concat(stream, extras)
-  defp maybe_concat(stream, false, _extras), do: stream
+  Emits the same candidates as `generate_permutations/2`, in the same order,
+  but one at a time: nothing is materialized up front, so consumers such as
+  `DomainTwistex.Twist.analyze_domain/2` can start resolving immediately.
+  Already-emitted fqdns are tracked in an ETS set owned by the enumerating
+  process, which is deleted when the stream finishes or is halted.
 
-  defp parse_domain(fqdn) do
-    case String.split(fqdn, ".", parts: 2) do
-      [domain, tld] -> {domain, tld}
-      _ -> {fqdn, "com"}
-    end
+  Accepts the same options as `generate_permutations/2`. Generators and
+  options are validated when the function is called, not when the stream
+  is run. With `depth: 2`, the ranked depth-2 candidates (at most
+  `:budget`) are computed in one go once the single-kind ones are exhausted.
+
+  ## Examples
+
+      iex> DomainTwistex.Permutate.stream("example.com") |> Enum.take(2)
+      [
+        %{
+          edit: %{original: "", position: 7, replacement: "a"},
+          fqdn: "examplea.com",
+          kind: "Addition",
+          kinds: ["Addition"],
+          tld: "com",
+          unicode: "examplea.com"
+        },
+        %{
+          edit: %{original: "", position: 7, replacement: "b"},
+          fqdn: "exampleb.com",
+          kind: "Addition",
+          kinds: ["Addition"],
+          tld: "com",
+          unicode: "exampleb.com"
+        }
+      ]
+  """
+  def stream(fqdn, opts \\ []) do
+    parts = parse_domain(fqdn)
+    all_generators = generators(opts)
+    limits = max_per_kind(opts, Enum.map(all_generators, & &1.kind()))
+    selected = select_generators(all_generators, opts, &enabled?(&1, opts))
+    classifiers = Enum.filter(selected, &function_exported?(&1, :classify, 3))
+    ranks = kind_ranks(all_generators)
+    steps = selected ++ depth_two(opts, all_generators)
+    opts = prepare_opts(steps, opts)
+
+    # One seen-set shared by all generators, which also counts candidates
+    # per primary kind; caps apply to unique candidates and stop a generator
+    # as soon as its own kind's cap is reached
+    Stream.transform(
+      steps,
+      fn -> :ets.new(:permutate_seen, [:set, :public]) end,
+      fn
+        {:depth_two, pairs, budget, seed_count}, seen ->
+          {second_order(parts, pairs, budget, seed_count, seen, opts), seen}
+
+        generator, seen ->
+          kind = generator.kind()
+
+          candidates =
+            generator.generate(parts, opts)
+            |> Stream.take_while(fn _ -> below_limit?(seen, kind, limits.(kind)) end)
+            |> Stream.flat_map(&encode_idna/1)
+            |> Stream.reject(&invalid_fqdn?/1)
+            |> Stream.reject(&:ets.member(seen, &1.fqdn))
+            |> Stream.map(&attribute(&1, generator, classifiers, ranks, parts, opts))
+            |> Stream.filter(&admit(&1, seen, limits))
+
+          {candidates, seen}
+      end,
+      &:ets.delete/1
+    )
   end
 
-  defp extract_tld(fqdn) do
-    case String.split(fqdn, ".", parts: 2) do
-      [_, tld] -> tld
-      _ -> ""
+  @doc """
+  Explains how a candidate domain relates to a protected domain.
+
+  Every registered kind checks whether it could have produced `candidate`
+  from `protected`, without generating the full permutation set. Kinds that
+  are disabled by default (FauxTld) are checked too; narrow the check with
+  `:only` and `:except`. Generators that do not implement
+  `c:DomainTwistex.Permutate.Generator.classify/3` are classified by
+  generating their candidates and comparing.
+
+  ## Parameters
+    - candidate: Suspicious domain, in Unicode or punycode form
+    - protected: The domain to protect
+    - opts: `:only`, `:except`, `:generators` and any generator options
+
+  ## Returns
+    - `{:ok, matches}` with one map per matching edit, each holding `:kind`
+      and edit details such as `:position`, `:original`, `:replacement` or
+      `:keyword`
+    - `:unrelated` when no kind produces the candidate, including when it is
+      the protected domain itself
+
+  ## Examples
+
+      iex> DomainTwistex.Permutate.classify("exmaple.com", "example.com")
+      {:ok, [%{kind: "Transposition", position: 2, original: "am", replacement: "ma"}]}
+
+      iex> DomainTwistex.Permutate.classify("example-login.net", "example.com")
+      :unrelated
+  """
+  def classify(candidate, protected, opts \\ []) do
+    parts = parse_domain(protected)
+
+    fqdn =
+      case IDNA.to_unicode(candidate) do
+        {:ok, unicode} -> unicode
+        {:error, _} -> candidate |> String.downcase() |> String.trim_trailing(".")
+      end
+
+    selected = opts |> generators() |> select_generators(opts, fn _generator -> true end)
+    opts = prepare_opts(selected, opts)
+
+    matches =
+      Enum.flat_map(selected, fn generator ->
+        generator
+        |> classify_with(fqdn, parts, opts)
+        |> Enum.map(&Map.put(&1, :kind, generator.kind()))
+      end)
+
+    case matches do
+      [] -> :unrelated
+      matches -> {:ok, matches}
     end
   end
 
-  defp char_lower(c) when c >= ?A and c <= ?Z, do: c + 32
-  defp char_lower(c), do: c
+  @doc """
+  Returns the generator modules used for the given options, in run order.
 
-  # --- Addition ---
+  Built-in generators come first, followed by those registered with
+  `config :domaintwistex, generators: [...]` and then those passed in the
+  `:generators` option. Every generator's `validate_opts/1` is called, and an
+  `ArgumentError` is raised for invalid options or modules that do not
+  implement `DomainTwistex.Permutate.Generator`.
+  """
+  def generators(opts \\ []) do
+    generators =
+      @builtin_generators ++
+        Application.get_env(:domaintwistex, :generators, []) ++
+        Keyword.get(opts, :generators, [])
+
+    Enum.each(generators, fn generator ->
+      unless generator?(generator) do
+        raise ArgumentError,
+              "#{inspect(generator)} does not implement DomainTwistex.Permutate.Generator"
+      end
 
-  defp addition(domain, tld) do
-    Stream.map(@ascii_lower, fn c ->
-      %{fqdn: "#{domain}#{c}.#{tld}", tld: tld, kind: "Addition"}
+      case generator.validate_opts(opts) do
+        :ok -> :ok
+        {:error, reason} -> raise ArgumentError, "#{inspect(generator)}: #{reason}"
+      end
     end)
+
+    Enum.uniq(generators)
   end
 
-  # --- Bitsquatting ---
+  @doc """
+  Returns the kind names produced by the generators for the given options.
 
-  defp bitsquatting(fqdn) do
-    chars = String.to_charlist(fqdn)
-    len = length(chars)
+  ## Examples
 
-    for c <- chars,
-        mask_index <- 0..7,
-        squatted = Bitwise.bxor(c, Bitwise.bsl(1, mask_index)),
-        squatted in ?a..?z or squatted in ?0..?9 or squatted == ?-,
-        idx <- 1..(len - 1) do
-      {before, after_chars} = Enum.split(chars, idx)
-      new_fqdn = List.to_string(before ++ [squatted] ++ after_chars)
-      %{fqdn: new_fqdn, tld: extract_tld(new_fqdn), kind: "Bitsquatting"}
-    end
+      iex> DomainTwistex.Permutate.kinds() |> Enum.take(3)
+      ["Addition", "Bitsquatting", "Hyphenation"]
+  """
+  def kinds(opts \\ []) do
+    opts |> generators() |> Enum.map(& &1.kind())
   end
 
-  # --- Hyphenation ---
+  @doc """
+  Counts, by kind, the candidates that generators with a candidate budget
+  left out for the domain and options, such as VowelShuffle beyond
+  `:vowel_shuffle_budget`. Only kinds that skipped candidates are listed.
 
-  defp hyphenation(fqdn) do
-    chars = String.to_charlist(fqdn)
+  ## Examples
 
-    Stream.unfold({chars, 1}, fn
-      {_, i} when i >= length(chars) -> nil
-      {c, i} -> {{c, i}, {c, i + 1}}
-    end)
-    |> Stream.map(fn {_c, i} ->
-      {before, after_chars} = Enum.split(chars, i)
-      new_fqdn = List.to_string(before ++ [?-] ++ after_chars)
-      %{fqdn: new_fqdn, tld: extract_tld(new_fqdn), kind: "Hyphenation"}
-    end)
+      iex> DomainTwistex.Permutate.skipped("aeiouaeiou.com", vowel_shuffle_budget: 100)
+      %{"VowelShuffle" => 15524}
+  """
+  def skipped(fqdn, opts \\ []) do
+    parts = parse_domain(fqdn)
+
+    for generator <- select_generators(generators(opts), opts, &enabled?(&1, opts)),
+        function_exported?(generator, :skipped, 2),
+        count = generator.skipped(parts, opts),
+        count > 0,
+        into: %{} do
+      {generator.kind(), count}
+    end
   end
 
-  # --- Hyphenation TLD Boundary ---
+  @doc """
+  Splits a domain into `{subdomain, label, suffix}` using the Public Suffix List.
 
-  defp hyphenation_tld_boundary(domain, tld) do
-    if String.contains?(tld, ".") do
-      [%{fqdn: "#{domain}-#{tld}", tld: tld, kind: "Hyphenation"}]
-    else
-      []
-    end
-  end
+  `xn--` labels are decoded first so that internationalized suffixes match
+  the list. Inputs without a registrable label fall back to splitting on the
+  first dot, and single labels get a `com` suffix.
+
+  ## Examples
 
-  # --- Insertion ---
+      iex> DomainTwistex.Permutate.parse_domain("login.example.co.uk")
+      {"login", "example", "co.uk"}
+  """
+  def parse_domain(fqdn) do
+    fqdn =
+      case IDNA.to_unicode(fqdn) do
+        {:ok, unicode} -> unicode
+        {:error, _} -> fqdn
+      end
 
-  defp insertion(fqdn) do
-    chars = String.to_charlist(fqdn)
-    len = length(chars)
+    case PublicSuffix.split(fqdn) do
+      {:ok, parts} ->
+        parts
 
-    for i <- 0..(len - 2),
-        layout <- @keyboard_layouts,
-        c = Enum.at(chars, i + 1),
-        adjacents = Map.get(layout, c, ""),
-        keyboard_char <- String.to_charlist(adjacents) do
-      {before, after_chars} = Enum.split(chars, i)
-      new_fqdn = List.to_string(before ++ [keyboard_char] ++ after_chars)
-      %{fqdn: new_fqdn, tld: extract_tld(new_fqdn), kind: "Insertion"}
+      :error ->
+        case fqdn |> String.downcase() |> String.trim_trailing(".") |> String.split(".", parts: 2) do
+          [label, suffix] -> {"", label, suffix}
+          [label] -> {"", label, "com"}
+        end
     end
   end
 
-  # --- Omission ---
+  defp invalid_fqdn?(%{fqdn: fqdn}), do: not Hostname.valid?(fqdn)
 
-  defp omission(fqdn) do
-    chars = String.to_charlist(fqdn)
+  # Applies :only and :except; generators named in neither are kept when
+  # `default` returns true for them
+  defp select_generators(generators, opts, default) do
+    known = Enum.map(generators, & &1.kind())
+    only = Keyword.get(opts, :only)
+    except = Keyword.get(opts, :except, [])
 
-    for i <- 0..(length(chars) - 1) do
-      new_fqdn = List.to_string(List.delete_at(chars, i))
-      %{fqdn: new_fqdn, tld: extract_tld(new_fqdn), kind: "Omission"}
-    end
+    validate_kinds!(only || [], known, :only)
+    validate_kinds!(except, known, :except)
+
+    Enum.filter(generators, fn generator ->
+      kind = generator.kind()
+
+      cond do
+        kind in except -> false
+        only != nil -> kind in only
+        true -> default.(generator)
+      end
+    end)
   end
 
-  # --- Repetition ---
+  # Returns a function from kind name to its cap (or nil for no cap)
+  defp max_per_kind(opts, known) do
+    case Keyword.get(opts, :max_per_kind) do
+      nil ->
+        fn _kind -> nil end
+
+      limit when is_integer(limit) and limit > 0 ->
+        fn _kind -> limit end
 
-  defp repetition(fqdn) do
-    chars = String.to_charlist(fqdn)
+      limits when is_map(limits) ->
+        validate_kinds!(Map.keys(limits), known, :max_per_kind)
 
-    for {c, i} <- Enum.with_index(chars),
-        c >= ?a and c <= ?z or c >= ?A and c <= ?Z do
-      {before, after_chars} = Enum.split(chars, i + 1)
-      new_fqdn = List.to_string(before ++ [c] ++ after_chars)
-      %{fqdn: new_fqdn, tld: extract_tld(new_fqdn), kind: "Repetition"}
+        unless Enum.all?(Map.values(limits), &(is_integer(&1) and &1 > 0)) do
+          raise ArgumentError, ":max_per_kind limits must be positive integers"
+        end
+
+        &Map.get(limits, &1)
+
+      other ->
+        raise ArgumentError,
+              ":max_per_kind must be a positive integer or a map of kind => limit, got: #{inspect(other)}"
     end
   end
 
-  # --- Replacement ---
+  defp validate_kinds!(kinds, known, key) do
+    unless is_list(kinds) do
+      raise ArgumentError, "#{inspect(key)} must be a list of kind names, got: #{inspect(kinds)}"
+    end
 
-  defp replacement(fqdn) do
-    chars = String.to_charlist(fqdn)
-    max = max(length(chars) - 2, 0)
+    case Enum.reject(kinds, &(&1 in known)) do
+      [] ->
+        :ok
 
-    for i <- 1..max,
-        layout <- @keyboard_layouts,
-        c = Enum.at(chars, i),
-        adjacents = Map.get(layout, c, ""),
-        keyboard_char <- String.to_charlist(adjacents) do
-      new_chars = List.replace_at(chars, i, keyboard_char)
-      new_fqdn = List.to_string(new_chars)
-      %{fqdn: new_fqdn, tld: extract_tld(new_fqdn), kind: "Replacement"}
+      unknown ->
+        raise ArgumentError,
+              "unknown kinds in #{inspect(key)}: #{inspect(unknown)}. Known kinds: #{Enum.join(known, ", ")}"
     end
   end
 
-  # --- Subdomain ---
+  # Kind name => priority rank, lower first
+  defp kind_ranks(generators) do
+    priority = @kind_priority |> Enum.with_index() |> Map.new()
+
+    generators
+    |> Enum.map(& &1.kind())
+    |> Enum.sort_by(&Map.get(priority, &1, map_size(priority)))
+    |> Enum.with_index()
+    |> Map.new()
+  end
+
+  # Lists every selected kind that also produces the candidate under :kinds,
+  # and makes the highest-priority one the primary :kind and :edit
+  defp attribute(candidate, generator, classifiers, ranks, parts, opts) do
+    fqdn = String.downcase(candidate.unicode)
+
+    others =
+      for other <- classifiers,
+          other != generator,
+          plausible?(other, fqdn, parts, opts),
+          edit <- Enum.take(other.classify(fqdn, parts, opts), 1) do
+        {other.kind(), edit}
+      end
+
+    matches =
+      Enum.sort_by(
+        [{generator.kind(), Map.get(candidate, :edit)} | others],
+        &Map.fetch!(ranks, elem(&1, 0))
+      )
 
-  defp subdomain(fqdn) do
-    chars = String.to_charlist(fqdn)
-    len = length(chars)
+    [{kind, edit} | _] = matches
+
+    candidate = candidate |> Map.put(:kind, kind) |> Map.put(:kinds, Enum.map(matches, &elem(&1, 0)))
+
+    if edit, do: Map.put(candidate, :edit, edit), else: Map.delete(candidate, :edit)
+  end
 
-    for i <- 0..max(len - 3, 0),
-        c1 = Enum.at(chars, i),
-        c2 = Enum.at(chars, i + 1, ?\0),
-        c1 != ?- and c1 != ?. and c2 != ?. do
-      {before, after_chars} = Enum.split(chars, i + 1)
-      new_fqdn = List.to_string(before ++ [?.] ++ after_chars)
-      %{fqdn: new_fqdn, tld: extract_tld(new_fqdn), kind: "Subdomain"}
+  # Emits a candidate unless its primary kind has reached its cap
+  defp admit(%{fqdn: fqdn, kind: kind}, seen, limits) do
+    if below_limit?(seen, kind, limits.(kind)) do
+      :ets.insert(seen, {fqdn})
+      :ets.update_counter(seen, {:count, kind}, 1, {{:count, kind}, 0})
+      true
+    else
+      false
     end
   end
 
-  # --- Transposition ---
+  defp below_limit?(_seen, _kind, nil), do: true
 
-  defp transposition(fqdn) do
-    chars = String.to_charlist(fqdn)
+  defp below_limit?(seen, kind, limit) do
+    case :ets.lookup(seen, {:count, kind}) do
+      [{_key, count}] -> count < limit
+      [] -> true
+    end
+  end
 
-    for i <- 0..(length(chars) - 2),
-        c1 = Enum.at(chars, i),
-        c2 = Enum.at(chars, i + 1),
-        c1 != c2 do
-      new_chars = List.replace_at(chars, i, c2) |> List.replace_at(i + 1, c1)
-      new_fqdn = List.to_string(new_chars)
-      %{fqdn: new_fqdn, tld: extract_tld(new_fqdn), kind: "Transposition"}
+  # Converts Unicode candidates to their ASCII form, dropping those that IDNA
+  # rejects, and records the display form alongside.
+  defp encode_idna(%{fqdn: fqdn} = candidate) do
+    if IDNA.ascii?(fqdn) do
+      [Map.put(candidate, :unicode, fqdn)]
+    else
+      with {:ok, ascii} <- IDNA.to_ascii(fqdn),
+           {:ok, unicode} <- IDNA.to_unicode(ascii),
+           {:ok, tld} <- IDNA.to_ascii(candidate.tld) do
+        [%{candidate | fqdn: ascii, tld: tld} |> Map.put(:unicode, unicode)]
+      else
+        {:error, _} -> []
+      end
     end
   end
 
-  # --- Vowel Swap ---
+  # --- Depth 2 ---
+
+  # Validates the depth-2 options and returns the extra stream step, if any
+  defp depth_two(opts, generators) do
+    case Keyword.get(opts, :depth, 1) do
+      1 ->
+        []
+
+      2 ->
+        by_kind = Map.new(generators, &{&1.kind(), &1})
+        known = Enum.map(generators, & &1.kind())
+        budget = positive_integer!(opts, :budget, @default_budget)
+        seed_count = positive_integer!(opts, :depth_seeds, @default_depth_seeds)
+
+        pairs =
+          opts
+          |> Keyword.get(:pairs, @default_pairs)
+          |> normalize_pairs(known)
+          |> Enum.map(fn {first, second, weight} ->
+            {Map.fetch!(by_kind, first), Map.fetch!(by_kind, second), weight}
+          end)
 
-  defp vowel_swap(fqdn) do
-    chars = String.to_charlist(fqdn)
+        [{:depth_two, pairs, budget, seed_count}]
 
-    for {c, i} <- Enum.with_index(chars),
-        char_lower(c) in @vowels,
-        vowel <- @vowels,
-        vowel != c do
-      new_fqdn = List.to_string(List.replace_at(chars, i, vowel))
-      %{fqdn: new_fqdn, tld: extract_tld(new_fqdn), kind: "VowelSwap"}
+      other ->
+        raise ArgumentError, ":depth must be 1 or 2, got: #{inspect(other)}"
     end
   end
 
-  # --- Vowel Shuffle ---
+  defp normalize_pairs(pairs, known) when is_list(pairs) do
+    pairs =
+      Enum.map(pairs, fn
+        {first, second} ->
+          {first, second, 1.0}
 
-  defp vowel_shuffle(domain, tld) do
-    domain_chars = String.to_charlist(domain)
-    vowel_positions = for {c, i} <- Enum.with_index(domain_chars), c in @vowels, do: i
-    n = min(length(vowel_positions), @vowel_shuffle_ceiling)
+        {first, second, weight} when is_number(weight) and weight > 0 ->
+          {first, second, weight}
 
-    if n == 0 do
-      []
-    else
-      products = cartesian_power(@vowels, n)
-
-      for replacement <- products do
-        label = domain_chars
-        |> Enum.with_index()
-        |> Enum.map(fn {c, i} ->
-          pos_idx = Enum.find_index(vowel_positions, &(&1 == i))
-          if pos_idx != nil and pos_idx < n do
-            Enum.at(replacement, pos_idx)
-          else
-            c
-          end
-        end)
-        %{fqdn: "#{List.to_string(label)}.#{tld}", tld: tld, kind: "VowelShuffle"}
-      end
-    end
+        other ->
+          raise ArgumentError,
+                ":pairs entries must be {first, second} or {first, second, weight}, got: #{inspect(other)}"
+      end)
+
+    pairs
+    |> Enum.flat_map(fn {first, second, _weight} -> [first, second] end)
+    |> validate_kinds!(known, :pairs)
+
+    pairs
   end
 
-  defp cartesian_power(list, n), do: cartesian_power(list, n, [[]])
-  defp cartesian_power(_list, 0, acc), do: acc
-  defp cartesian_power(list, n, acc) do
-    new_acc = for item <- list, rest <- acc, do: [item | rest]
-    cartesian_power(list, n - 1, new_acc)
+  defp normalize_pairs(other, _known) do
+    raise ArgumentError, ":pairs must be a list of kind pairs, got: #{inspect(other)}"
   end
 
-  # --- Double Vowel Insertion ---
+  defp positive_integer!(opts, key, default) do
+    case Keyword.get(opts, key, default) do
+      value when is_integer(value) and value > 0 -> value
+      value -> raise ArgumentError, "#{inspect(key)} must be a positive integer, got: #{inspect(value)}"
+    end
+  end
 
-  defp double_vowel_insertion(fqdn) do
-    chars = String.to_charlist(fqdn)
+  # Applies every pair's second kind to the most plausible candidates of its
+  # first kind, keeping the `budget` best-scoring results in a bounded set
+  defp second_order(parts, pairs, budget, seed_count, seen, opts) do
+    input = Generator.build(parts, nil).fqdn
+    cache = :ets.new(:permutate_similarity, [:set, :private])
+
+    try do
+      seeds =
+        pairs
+        |> Enum.map(&elem(&1, 0))
+        |> Enum.uniq()
+        |> Map.new(&{&1, seeds(&1, parts, input, seed_count, cache, opts)})
+
+      top =
+        for {first, second, weight} <- pairs,
+            seed <- Map.fetch!(seeds, first),
+            candidate <- second.generate(parse_domain(seed.unicode), opts),
+            candidate.fqdn != input,
+            reduce: {:gb_sets.new(), %{}} do
+          top ->
+            candidate =
+              candidate
+              |> Map.put(:kind, [first.kind(), second.kind()])
+              |> Map.put(:edit, [Map.get(seed, :edit), Map.get(candidate, :edit)])
+
+            offer(top, candidate, weight * similarity(candidate, parts, cache), budget, seen)
+        end
 
-    for i <- 0..(length(chars) - 2),
-        c1 = Enum.at(chars, i),
-        c2 = Enum.at(chars, i + 1),
-        char_lower(c1) in @vowels and char_lower(c2) in @vowels,
-        inserted <- @vowels do
-      {before, after_chars} = Enum.split(chars, i + 1)
-      new_fqdn = List.to_string(before ++ [inserted] ++ after_chars)
-      %{fqdn: new_fqdn, tld: extract_tld(new_fqdn), kind: "DoubleVowelInsertion"}
+      top
+      |> elem(0)
+      |> :gb_sets.to_list()
+      |> Enum.reverse()
+      |> Enum.map(fn {_score, _fqdn, candidate} -> Map.put(candidate, :kinds, [candidate.kind]) end)
+      |> Enum.filter(&:ets.insert_new(seen, {&1.fqdn}))
+    after
+      :ets.delete(cache)
     end
   end
 
-  # --- Keyword ---
+  # The most plausible valid candidates of a kind, used as depth-2 inputs
+  defp seeds(generator, parts, input, count, cache, opts) do
+    parts
+    |> generator.generate(opts)
+    |> Stream.reject(&(&1.fqdn == input))
+    |> Enum.sort_by(&similarity(&1, parts, cache), :desc)
+    |> Stream.flat_map(&encode_idna/1)
+    |> Stream.reject(&invalid_fqdn?/1)
+    |> Enum.take(count)
+  end
 
-  defp keyword(domain, tld) do
-    for kw <- @keywords do
-      [
-        %{fqdn: "#{domain}-#{kw}.#{tld}", tld: tld, kind: "Keyword"},
-        %{fqdn: "#{domain}#{kw}.#{tld}", tld: tld, kind: "Keyword"},
-        %{fqdn: "#{kw}-#{domain}.#{tld}", tld: tld, kind: "Keyword"},
-        %{fqdn: "#{kw}#{domain}.#{tld}", tld: tld, kind: "Keyword"}
-      ]
+  # IDNA encoding and validation only run for candidates that would make
+  # the cut, since most depth-2 candidates score too low
+  defp offer({set, fqdns} = top, candidate, score, budget, seen) do
+    if :gb_sets.size(set) >= budget and score <= elem(:gb_sets.smallest(set), 0) do
+      top
+    else
+      with [encoded] <- encode_idna(candidate),
+           false <- invalid_fqdn?(encoded),
+           false <- Map.has_key?(fqdns, encoded.fqdn),
+           false <- :ets.member(seen, encoded.fqdn) do
+        set = :gb_sets.add({score, encoded.fqdn, encoded}, set)
+        fqdns = Map.put(fqdns, encoded.fqdn, true)
+
+        if :gb_sets.size(set) > budget do
+          {{_score, evicted, _candidate}, set} = :gb_sets.take_smallest(set)
+          {set, Map.delete(fqdns, evicted)}
+        else
+          {set, fqdns}
+        end
+      else
+        _ -> top
+      end
     end
-    |> List.flatten()
   end
 
-  # --- TLD ---
+  # Similarity of an unencoded candidate to the input: mostly the name
+  # (subdomain and label), partly the suffix. Memoised, since Tld-style
+  # kinds repeat the same names and suffixes across seeds.
+  defp similarity(%{fqdn: fqdn, tld: tld}, {subdomain, label, suffix}, cache) do
+    name = String.replace_suffix(fqdn, "." <> tld, "")
+    original = if subdomain == "", do: label, else: "#{subdomain}.#{label}"
 
-  defp tld(domain) do
-    Stream.map(@tlds, fn tld ->
-      %{fqdn: "#{domain}.#{tld}", tld: tld, kind: "Tld"}
-    end)
+    0.75 * cached_jaro(cache, name, original) + 0.25 * cached_jaro(cache, tld, suffix)
   end
 
-  # --- Faux TLD ---
+  defp cached_jaro(cache, left, right) do
+    case :ets.lookup(cache, {left, right}) do
+      [{_key, distance}] ->
+        distance
 
-  defp faux_tld(domain, tld) do
-    for tld_var <- @tlds do
-      faux = String.replace(tld_var, ".", "-")
-      [
-        %{fqdn: "#{domain}-#{faux}.#{tld}", tld: tld, kind: "FauxTld"},
-        %{fqdn: "#{domain}#{faux}.#{tld}", tld: tld, kind: "FauxTld"}
-      ]
+      [] ->
+        distance = String.jaro_distance(left, right)
+        :ets.insert(cache, {{left, right}, distance})
+        distance
     end
-    |> List.flatten()
   end
 
-  # --- Mapped ---
+  defp classify_with(generator, fqdn, parts, opts) do
+    cond do
+      not plausible?(generator, fqdn, parts, opts) ->
+        []
+
+      function_exported?(generator, :classify, 3) ->
+        generator.classify(fqdn, parts, opts)
+
+      true ->
+        parts
+        |> generator.generate(opts)
+        |> Stream.flat_map(&encode_idna/1)
+        |> Enum.filter(&(&1.unicode == fqdn))
+        |> Enum.take(1)
+        |> Enum.map(&Map.get(&1, :edit, %{}))
+    end
+  end
 
-  defp mapped(domain, tld) do
-    Enum.flat_map(@mapped, fn
-      {key, values} when key in ~w(ck oo) ->
-        if String.contains?(domain, key) do
-          Enum.map(values, fn mapped_value ->
-            new_domain = String.replace(domain, key, mapped_value)
-            %{fqdn: "#{new_domain}.#{tld}", tld: tld, kind: "Mapped"}
-          end)
-        else
-          []
-        end
+  # Lets the generators of a run resolve shared data once, not per candidate
+  defp prepare_opts(steps, opts) do
+    steps
+    |> Enum.flat_map(fn
+      {:depth_two, pairs, _budget, _seed_count} ->
+        Enum.flat_map(pairs, fn {first, second, _weight} -> [first, second] end)
 
-      {key, values} ->
-        if String.contains?(domain, key) do
-          Enum.flat_map(values, fn mapped_value ->
-            new_domain = String.replace(domain, key, mapped_value)
-            [%{fqdn: "#{new_domain}.#{tld}", tld: tld, kind: "Mapped"}]
-          end)
-        else
-          []
-        end
+      generator ->
+        [generator]
     end)
+    |> Enum.uniq()
+    |> Enum.filter(&function_exported?(&1, :prepare_opts, 1))
+    |> Enum.reduce(opts, & &1.prepare_opts(&2))
   end
 
-  # --- Homoglyph ---
+  defp plausible?(generator, fqdn, parts, opts) do
+    not function_exported?(generator, :plausible?, 3) or generator.plausible?(fqdn, parts, opts)
+  end
 
-  defp homoglyph(fqdn) do
-    chars = String.to_charlist(fqdn)
+  defp generator?(module) do
+    Code.ensure_loaded?(module) and function_exported?(module, :kind, 0) and
+      function_exported?(module, :generate, 2) and function_exported?(module, :validate_opts, 1)
+  end
 
-    for {c, i} <- Enum.with_index(chars),
-        glyphs <- [Map.get(@homoglyphs, c, [])],
-        g <- glyphs do
-      new_fqdn = List.to_string(List.replace_at(chars, i, g))
-      %{fqdn: new_fqdn, tld: extract_tld(new_fqdn), kind: "Homoglyph"}
-    end
+  defp enabled?(generator, opts) do
+    not function_exported?(generator, :enabled?, 1) or generator.enabled?(opts)
   end
-end
\ No newline at end of file
+end
@@ -0,0 +1,343 @@
+defmodule DomainTwistex.Permutate.Generator do
+  @moduledoc """
+  Behaviour for permutation generators.
+
+  Every permutation kind (Addition, Homoglyph, Tld, ...) is a module
+  implementing this behaviour. `DomainTwistex.Permutate` runs the built-in
+  generators plus any registered through application config or the
+  `:generators` option:
+
+      # config/config.exs
+      config :domaintwistex, generators: [MyApp.BrandSuffixGenerator]
+
+      # or per call
+      DomainTwistex.Permutate.generate_permutations("example.com",
+        generators: [MyApp.BrandSuffixGenerator]
+      )
+
+  ## Example
+
+      defmodule MyApp.BrandSuffixGenerator do
+        use DomainTwistex.Permutate.Generator
+
+        @impl true
+        def kind, do: "BrandSuffix"
+
+        @impl true
+        def generate({_subdomain, label, _suffix} = parts, opts) do
+          for suffix <- Keyword.get(opts, :brand_suffixes, ["hq", "corp"]) do
+            candidate(parts, label <> suffix, kind())
+          end
+        end
+
+        @impl true
+        def validate_opts(opts) do
+          case Keyword.get(opts, :brand_suffixes, []) do
+            list when is_list(list) -> :ok
+            _ -> {:error, ":brand_suffixes must be a list of strings"}
+          end
+        end
+      end
+
+  Generators receive the input split into `{subdomain, label, suffix}` (see
+  `DomainTwistex.Permutate.parse_domain/1`) and return any enumerable of
+  candidate maps with at least `:fqdn`, `:tld` and `:kind`. Candidates may be
+  Unicode; IDNA encoding, validation and deduplication happen afterwards.
+
+  Candidates should also record the edit that produced them under `:edit`
+  (see `DomainTwistex.Permutate.Edit`), by passing it as the last argument
+  of `candidate/4` or `build/3`.
+
+  Character-level generators cut the label with `splits/1` and build each
+  candidate with a single binary concatenation; their classifiers locate
+  the edit with `insertions/2`, `deletions/2` and `difference/2`, which run
+  in linear time. All are imported by `use DomainTwistex.Permutate.Generator`.
+
+  Generators may also implement `c:classify/3` to recognise their own output
+  without generating it, which `DomainTwistex.Permutate.classify/3` uses.
+  Generators without it are classified by generating and comparing.
+  """
+
+  @typedoc "Input domain split into subdomain, registrable label and public suffix."
+  @type parts :: {subdomain :: String.t(), label :: String.t(), suffix :: String.t()}
+
+  @typedoc "A generated permutation."
+  @type candidate :: %{
+          required(:fqdn) => String.t(),
+          required(:tld) => String.t(),
+          required(:kind) => String.t(),
+          optional(:edit) => DomainTwistex.Permutate.Edit.t(),
+          optional(atom()) => term()
+        }
+
+  @doc "Name of the permutation kind, used as the `:kind` of every candidate."
+  @callback kind() :: String.t()
+
+  @doc "Generates candidates for the parsed input domain."
+  @callback generate(parts(), opts :: keyword()) :: Enumerable.t()
+
+  @doc "Validates the options this generator reads. Called before generation."
+  @callback validate_opts(opts :: keyword()) :: :ok | {:error, String.t()}
+
+  @doc "Whether the generator runs for the given options (default: always)."
+  @callback enabled?(opts :: keyword()) :: boolean()
+
+  @doc """
+  Returns how the generator turns the protected domain into `fqdn`, one edit
+  per match, or `[]` when it cannot produce `fqdn`.
+
+  `fqdn` is the candidate in lowercase Unicode form and `parts` is the parsed
+  protected domain. Edits have the same shape as the `:edit` of generated
+  candidates.
+  """
+  @callback classify(fqdn :: String.t(), parts(), opts :: keyword()) :: [
+              DomainTwistex.Permutate.Edit.t()
+            ]
+
+  @doc """
+  Number of candidates `generate/2` leaves out for the input, for
+  generators with a candidate budget (VowelShuffle). Reported by
+  `DomainTwistex.Permutate.skipped/2`.
+  """
+  @callback skipped(parts(), opts :: keyword()) :: non_neg_integer()
+
+  @doc """
+  A cheap test of whether `classify/3` can match `fqdn`, such as a length or
+  suffix check. Candidates of other kinds are only classified when it
+  returns true, so generators with a costly `classify/3` should implement it.
+  """
+  @callback plausible?(fqdn :: String.t(), parts(), opts :: keyword()) :: boolean()
+
+  @doc """
+  Resolves data every candidate of a run shares, such as a keyword list
+  read from a file, and returns the options with it added. Called once per
+  `DomainTwistex.Permutate.stream/2` or `DomainTwistex.Permutate.classify/3`
+  call, after validation; `generate/2` and `classify/3` then receive the
+  returned options.
+  """
+  @callback prepare_opts(opts :: keyword()) :: keyword()
+
+  @optional_callbacks enabled?: 1, classify: 3, plausible?: 3, prepare_opts: 1, skipped: 2
+
+  defmacro __using__(_opts) do
+    quote do
+      @behaviour DomainTwistex.Permutate.Generator
+
+      import DomainTwistex.Permutate.Generator,
+        only: [
+          candidate: 3,
+          candidate: 4,
+          build: 2,
+          build: 3,
+          mutated_label: 2,
+          splits: 1,
+          insertions: 2,
+          deletions: 2,
+          difference: 2,
+          fold_suffix: 1
+        ],
+        warn: false
+
+      @impl DomainTwistex.Permutate.Generator
+      def validate_opts(_opts), do: :ok
+
+      @impl DomainTwistex.Permutate.Generator
+      def enabled?(_opts), do: true
+
+      defoverridable validate_opts: 1, enabled?: 1
+    end
+  end
+
+  @doc """
+  Builds a candidate from a new registrable label, keeping the original
+  subdomain and suffix intact. The optional `edit` is stored under `:edit`.
+  """
+  @spec candidate(parts(), String.t(), String.t(), DomainTwistex.Permutate.Edit.t() | nil) ::
+          candidate()
+  def candidate({subdomain, _label, suffix}, new_label, kind, edit \\ nil) do
+    build({subdomain, new_label, suffix}, kind, edit)
+  end
+
+  @doc """
+  Builds a candidate from complete `{subdomain, label, suffix}` parts, for
+  generators that also change the suffix.
+  """
+  @spec build(parts(), String.t(), DomainTwistex.Permutate.Edit.t() | nil) :: candidate()
+  def build(parts, kind, edit \\ nil)
+
+  def build({"", label, suffix}, kind, edit) do
+    put_edit(%{fqdn: "#{label}.#{suffix}", tld: suffix, kind: kind}, edit)
+  end
+
+  def build({subdomain, label, suffix}, kind, edit) do
+    put_edit(%{fqdn: "#{subdomain}.#{label}.#{suffix}", tld: suffix, kind: kind}, edit)
+  end
+
+  defp put_edit(candidate, nil), do: candidate
+  defp put_edit(candidate, edit), do: Map.put(candidate, :edit, edit)
+
+  @doc """
+  Extracts the registrable label of `fqdn` when it keeps the subdomain and
+  suffix of `parts`, as every candidate built with `candidate/3` does.
+
+  The label may contain dots (Subdomain candidates).
+
+  ## Examples
+
+      iex> DomainTwistex.Permutate.Generator.mutated_label("login.exmaple.co.uk", {"login", "example", "co.uk"})
+      {:ok, "exmaple"}
+
+      iex> DomainTwistex.Permutate.Generator.mutated_label("example.net", {"", "example", "com"})
+      :error
+  """
+  @spec mutated_label(String.t(), parts()) :: {:ok, String.t()} | :error
+  def mutated_label(fqdn, {subdomain, _label, suffix}) do
+    prefix = if subdomain == "", do: "", else: subdomain <> "."
+    tail = "." <> suffix
+    size = byte_size(fqdn) - byte_size(prefix) - byte_size(tail)
+
+    if size > 0 and String.starts_with?(fqdn, prefix) and String.ends_with?(fqdn, tail) do
+      {:ok, binary_part(fqdn, byte_size(prefix), size)}
+    else
+      :error
+    end
+  end
+
+  @doc """
+  Cuts a label at each of its characters, as `{index, before, char, rest}`
+  tuples: the character index, the binary before it, its code point and the
+  binary after it.
+
+  The cuts are sub-binaries of `label`, so generators build each candidate
+  with one concatenation (`before <> replacement <> rest`) instead of
+  walking a charlist per position.
+
+  ## Examples
+
+      iex> DomainTwistex.Permutate.Generator.splits("añb")
+      [{0, "", ?a, "ñb"}, {1, "a", ?ñ, "b"}, {2, "añ", ?b, ""}]
+  """
+  @spec splits(String.t()) :: [{non_neg_integer(), String.t(), char(), String.t()}]
+  def splits(label), do: splits(label, label, 0, [])
+
+  defp splits(label, <<c::utf8, rest::binary>>, index, acc) do
+    before = binary_part(label, 0, byte_size(label) - byte_size(rest) - byte_size(<<c::utf8>>))
+    splits(label, rest, index + 1, [{index, before, c, rest} | acc])
+  end
+
+  defp splits(_label, <<>>, _index, acc), do: Enum.reverse(acc)
+
+  @doc """
+  Positions at which inserting one character into `label` gives
+  `new_label`, with the inserted character, as `[{index, char}]`.
+
+  Several positions match when the character extends a run (`gooogle` from
+  `google`). Classifiers of insertion kinds filter these by their own rules.
+
+  ## Examples
+
+      iex> DomainTwistex.Permutate.Generator.insertions("google", "gooogle")
+      [{1, ?o}, {2, ?o}, {3, ?o}]
+  """
+  @spec insertions(String.t(), String.t()) :: [{non_neg_integer(), char()}]
+  def insertions(label, new_label) do
+    chars = String.to_charlist(label)
+    new_chars = String.to_charlist(new_label)
+    n = length(chars)
+
+    if length(new_chars) == n + 1 do
+      {prefix, suffix} = affixes(chars, new_chars)
+      indexed(new_chars, max(0, n - suffix), min(prefix, n))
+    else
+      []
+    end
+  end
+
+  @doc """
+  Positions at which deleting one character of `label` gives `new_label`,
+  with the deleted character, as `[{index, char}]`.
+
+  ## Examples
+
+      iex> DomainTwistex.Permutate.Generator.deletions("google", "gogle")
+      [{1, ?o}, {2, ?o}]
+  """
+  @spec deletions(String.t(), String.t()) :: [{non_neg_integer(), char()}]
+  def deletions(label, new_label) do
+    chars = String.to_charlist(label)
+    new_chars = String.to_charlist(new_label)
+    n = length(chars)
+
+    if length(new_chars) == n - 1 do
+      {prefix, suffix} = affixes(chars, new_chars)
+      indexed(chars, max(0, n - 1 - suffix), min(prefix, n - 1))
+    else
+      []
+    end
+  end
+
+  @doc """
+  The label with the suffix run into it, the dot before the suffix dropped
+  or typed as a hyphen, as `{new_label, separator}` tuples. Various keeps
+  the folds on the original suffix and TldTypo moves them to other TLDs.
+
+  ## Examples
+
+      iex> DomainTwistex.Permutate.Generator.fold_suffix({"", "example", "co.uk"})
+      [{"examplecouk", ""}, {"example-co-uk", "-"}]
+  """
+  @spec fold_suffix(parts()) :: [{String.t(), String.t()}]
+  def fold_suffix({_subdomain, label, suffix}) do
+    for separator <- ["", "-"] do
+      {label <> separator <> String.replace(suffix, ".", separator), separator}
+    end
+  end
+
+  @doc """
+  The span where two labels differ, once their common prefix and suffix are
+  removed: `{index, removed, added}` with the removed and added characters
+  as charlists, or `:same` for equal labels.
+
+  ## Examples
+
+      iex> DomainTwistex.Permutate.Generator.difference("example", "exmaple")
+      {2, ~c"am", ~c"ma"}
+  """
+  @spec difference(String.t(), String.t()) :: {non_neg_integer(), charlist(), charlist()} | :same
+  def difference(label, label), do: :same
+
+  def difference(label, new_label) do
+    chars = String.to_charlist(label)
+    new_chars = String.to_charlist(new_label)
+    {prefix, suffix} = affixes(chars, new_chars)
+    suffix = Enum.min([suffix, length(chars) - prefix, length(new_chars) - prefix])
+
+    removed = chars |> Enum.drop(prefix) |> Enum.drop(-suffix)
+    added = new_chars |> Enum.drop(prefix) |> Enum.drop(-suffix)
+    {prefix, removed, added}
+  end
+
+  # Lengths of the common prefix and of the common suffix, which may overlap
+  defp affixes(chars, new_chars) do
+    {common_length(chars, new_chars, 0), common_length(Enum.reverse(chars), Enum.reverse(new_chars), 0)}
+  end
+
+  defp common_length([c | rest], [c | new_rest], n), do: common_length(rest, new_rest, n + 1)
+  defp common_length(_chars, _new_chars, n), do: n
+
+  defp indexed(chars, first, last) when first <= last do
+    for {c, i} <- chars |> Enum.slice(first..last) |> Enum.with_index(first), do: {i, c}
+  end
+
+  defp indexed(_chars, _first, _last), do: []
+
+  @doc """
+  Validates that an option, when given, is a boolean.
+  """
+  def validate_boolean(opts, key) do
+    case Keyword.get(opts, key) do
+      value when is_boolean(value) or is_nil(value) -> :ok
+      value -> {:error, "#{inspect(key)} must be a boolean, got: #{inspect(value)}"}
+    end
+  end
+end
@@ -0,0 +1,33 @@
+defmodule DomainTwistex.Permutate.Generators.Addition do
+  @moduledoc """
+  Appends each ASCII letter to the label (`example` -> `examplea`).
+  """
+
+  use DomainTwistex.Permutate.Generator
+
+  alias DomainTwistex.Permutate.Data
+
+  @ascii_lower Data.ascii_lower()
+
+  @impl true
+  def kind, do: "Addition"
+
+  @impl true
+  def generate({_subdomain, label, _suffix} = parts, _opts) do
+    position = length(String.to_charlist(label))
+
+    Stream.map(@ascii_lower, fn c ->
+      candidate(parts, "#{label}#{<<c>>}", kind(), %{position: position, original: "", replacement: <<c>>})
+    end)
+  end
+
+  @impl true
+  def classify(fqdn, {_subdomain, label, _suffix} = parts, _opts) do
+    with {:ok, new_label} <- mutated_label(fqdn, parts),
+         {^label, <<c>>} when c in @ascii_lower <- String.split_at(new_label, -1) do
+      [%{position: length(String.to_charlist(label)), original: "", replacement: <<c>>}]
+    else
+      _ -> []
+    end
+  end
+end
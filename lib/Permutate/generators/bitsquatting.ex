@@ -0,0 +1,41 @@
+defmodule DomainTwistex.Permutate.Generators.Bitsquatting do
+  @moduledoc """
+  Flips one bit of a label character in place (`dxample` for `example`, bit
+  0 of `e`), the name a single memory or transmission error turns the
+  original into. Only flips giving a valid host name character (`a-z`,
+  `0-9`, `-`) are kept, as in dnstwist. Edits record the flipped `:bit`
+  (0-7) of the `:original` character.
+  """
+
+  use DomainTwistex.Permutate.Generator
+
+  @bits 0..7
+
+  @impl true
+  def kind, do: "Bitsquatting"
+
+  @impl true
+  def generate({_subdomain, label, _suffix} = parts, _opts) do
+    for {i, before, c, rest} <- splits(label),
+        bit <- @bits,
+        squatted = Bitwise.bxor(c, Bitwise.bsl(1, bit)),
+        ldh?(squatted) do
+      edit = %{position: i, original: <<c::utf8>>, replacement: <<squatted>>, bit: bit}
+      candidate(parts, before <> <<squatted>> <> rest, kind(), edit)
+    end
+  end
+
+  @impl true
+  def classify(fqdn, {_subdomain, label, _suffix} = parts, _opts) do
+    with {:ok, new_label} <- mutated_label(fqdn, parts),
+         {i, [c], [squatted]} <- difference(label, new_label),
+         true <- ldh?(squatted),
+         bit when bit != nil <- Enum.find(@bits, &(Bitwise.bxor(c, squatted) == Bitwise.bsl(1, &1))) do
+      [%{position: i, original: <<c::utf8>>, replacement: <<squatted>>, bit: bit}]
+    else
+      _ -> []
+    end
+  end
+
+  defp ldh?(c), do: c in ?a..?z or c in ?0..?9 or c == ?-
+end
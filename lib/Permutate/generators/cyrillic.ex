@@ -0,0 +1,48 @@
+defmodule DomainTwistex.Permutate.Generators.Cyrillic do
+  @moduledoc """
+  Replaces every Latin letter that has a Cyrillic look-alike in one go
+  (`аррӏе` for `apple`), the whole-script homograph attack. Letters without a
+  Cyrillic counterpart are kept, so the label may mix scripts. Look-alikes
+  come from `DomainTwistex.Permutate.Confusables`.
+  """
+
+  use DomainTwistex.Permutate.Generator
+
+  alias DomainTwistex.Permutate.Confusables
+
+  @cyrillic (for {c, glyphs} <- Confusables.lookalikes(),
+                 glyph = Enum.find(glyphs, &String.match?(<<&1::utf8>>, ~r/\p{Cyrillic}/u)),
+                 glyph != nil,
+                 into: %{} do
+               {c, glyph}
+             end)
+
+  @impl true
+  def kind, do: "Cyrillic"
+
+  @impl true
+  def generate({_subdomain, label, _suffix} = parts, _opts) do
+    chars = String.to_charlist(label)
+    replaced = transliterate(chars)
+
+    if replaced == chars do
+      []
+    else
+      new_label = List.to_string(replaced)
+      [candidate(parts, new_label, kind(), %{original: label, replacement: new_label})]
+    end
+  end
+
+  @impl true
+  def classify(fqdn, {_subdomain, label, _suffix} = parts, _opts) do
+    with {:ok, new_label} <- mutated_label(fqdn, parts),
+         true <- new_label != label,
+         true <- String.to_charlist(new_label) == transliterate(String.to_charlist(label)) do
+      [%{original: label, replacement: new_label}]
+    else
+      _ -> []
+    end
+  end
+
+  defp transliterate(chars), do: Enum.map(chars, &Map.get(@cyrillic, &1, &1))
+end
@@ -0,0 +1,56 @@
+defmodule DomainTwistex.Permutate.Generators.DoubleVowelInsertion do
+  @moduledoc """
+  Inserts a vowel between two adjacent vowels (`goaogle`).
+
+  Disabled with `double_vowel: false`.
+  """
+
+  use DomainTwistex.Permutate.Generator
+
+  alias DomainTwistex.Permutate.Data
+  alias DomainTwistex.Permutate.Generator
+
+  @vowels Data.vowels()
+
+  @impl true
+  def kind, do: "DoubleVowelInsertion"
+
+  @impl true
+  def enabled?(opts), do: Keyword.get(opts, :double_vowel, true)
+
+  @impl true
+  def validate_opts(opts), do: Generator.validate_boolean(opts, :double_vowel)
+
+  @impl true
+  def generate({_subdomain, label, _suffix} = parts, _opts) do
+    cuts = splits(label)
+
+    for {{i, before, c1, _}, {_, _, c2, rest}} <- Enum.zip(cuts, Enum.drop(cuts, 1)),
+        char_lower(c1) in @vowels and char_lower(c2) in @vowels,
+        inserted <- @vowels do
+      edit = %{position: i + 1, original: "", replacement: <<inserted>>}
+      candidate(parts, before <> <<c1, inserted, c2>> <> rest, kind(), edit)
+    end
+  end
+
+  @impl true
+  def classify(fqdn, {_subdomain, label, _suffix} = parts, _opts) do
+    case mutated_label(fqdn, parts) do
+      {:ok, new_label} ->
+        chars = List.to_tuple(String.to_charlist(label))
+
+        for {i, inserted} <- insertions(label, new_label),
+            i in 1..(tuple_size(chars) - 1)//1,
+            inserted in @vowels,
+            char_lower(elem(chars, i - 1)) in @vowels and char_lower(elem(chars, i)) in @vowels do
+          %{position: i, original: "", replacement: <<inserted>>}
+        end
+
+      :error ->
+        []
+    end
+  end
+
+  defp char_lower(c) when c >= ?A and c <= ?Z, do: c + 32
+  defp char_lower(c), do: c
+end
@@ -0,0 +1,66 @@
+defmodule DomainTwistex.Permutate.Generators.FauxTld do
+  @moduledoc """
+  Appends TLD-like strings to the label (`example-co-uk.com`).
+
+  Disabled by default; enable with `faux_tld: true` (adds ~14K entries).
+  """
+
+  use DomainTwistex.Permutate.Generator
+
+  alias DomainTwistex.Permutate.Data
+  alias DomainTwistex.Permutate.Generator
+
+  # Known suffixes have at most four dots or hyphens; a faux TLD with more
+  # than this is not checked, to bound the 2^n dot/hyphen readings
+  @max_separators 8
+
+  @impl true
+  def kind, do: "FauxTld"
+
+  @impl true
+  def enabled?(opts), do: Keyword.get(opts, :faux_tld, false)
+
+  @impl true
+  def validate_opts(opts), do: Generator.validate_boolean(opts, :faux_tld)
+
+  @impl true
+  def generate({_subdomain, label, _suffix} = parts, _opts) do
+    position = length(String.to_charlist(label))
+
+    for tld_var <- Data.tlds() do
+      faux = String.replace(tld_var, ".", "-")
+      [
+        candidate(parts, "#{label}-#{faux}", kind(), %{position: position, original: "", replacement: "-" <> faux}),
+        candidate(parts, "#{label}#{faux}", kind(), %{position: position, original: "", replacement: faux})
+      ]
+    end
+    |> List.flatten()
+  end
+
+  @impl true
+  def classify(fqdn, {_subdomain, label, _suffix} = parts, _opts) do
+    case mutated_label(fqdn, parts) do
+      {:ok, new_label} ->
+        for separator <- ["-", ""],
+            String.starts_with?(new_label, label <> separator),
+            faux = String.replace_prefix(new_label, label <> separator, ""),
+            known_tld?(faux) do
+          %{position: length(String.to_charlist(label)), original: "", replacement: separator <> faux}
+        end
+
+      :error ->
+        []
+    end
+  end
+
+  # Whether some known TLD reads as `faux` once its dots become hyphens
+  defp known_tld?(faux) do
+    parts = String.split(faux, "-")
+
+    length(parts) <= @max_separators + 1 and
+      parts |> readings() |> Enum.any?(&MapSet.member?(Data.tld_set(), &1))
+  end
+
+  defp readings([part]), do: [part]
+  defp readings([part | rest]), do: for(tail <- readings(rest), separator <- [".", "-"], do: part <> separator <> tail)
+end
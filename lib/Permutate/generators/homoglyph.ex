@@ -0,0 +1,41 @@
+defmodule DomainTwistex.Permutate.Generators.Homoglyph do
+  @moduledoc """
+  Substitutes Unicode look-alike characters (`gögle`, `gооgle` with Cyrillic
+  `о`). Glyphs come from the curated Latin table in
+  `DomainTwistex.Permutate.Data` and the Unicode confusables data in
+  `DomainTwistex.Permutate.Confusables`. Candidates are emitted in Unicode
+  and punycode-encoded by `DomainTwistex.Permutate`.
+  """
+
+  use DomainTwistex.Permutate.Generator
+
+  alias DomainTwistex.Permutate.Confusables
+  alias DomainTwistex.Permutate.Data
+
+  @homoglyphs Map.merge(Data.homoglyphs(), Confusables.lookalikes(), fn _c, curated, confusables ->
+                Enum.uniq(curated ++ confusables)
+              end)
+
+  @impl true
+  def kind, do: "Homoglyph"
+
+  @impl true
+  def generate({_subdomain, label, _suffix} = parts, _opts) do
+    for {i, before, c, rest} <- splits(label),
+        g <- Map.get(@homoglyphs, c, []) do
+      edit = %{position: i, original: <<c::utf8>>, replacement: <<g::utf8>>}
+      candidate(parts, before <> <<g::utf8>> <> rest, kind(), edit)
+    end
+  end
+
+  @impl true
+  def classify(fqdn, {_subdomain, label, _suffix} = parts, _opts) do
+    with {:ok, new_label} <- mutated_label(fqdn, parts),
+         {i, [c], [g]} <- difference(label, new_label),
+         true <- g in Map.get(@homoglyphs, c, []) do
+      [%{position: i, original: <<c::utf8>>, replacement: <<g::utf8>>}]
+    else
+      _ -> []
+    end
+  end
+end
@@ -0,0 +1,53 @@
+defmodule DomainTwistex.Permutate.Generators.Hyphenation do
+  @moduledoc """
+  Inserts a hyphen between label characters (`exa-mple`), and across the
+  boundary of a multi-label suffix (`example-co.uk`).
+  """
+
+  use DomainTwistex.Permutate.Generator
+
+  @impl true
+  def kind, do: "Hyphenation"
+
+  @impl true
+  def generate(parts, _opts) do
+    Stream.concat(hyphenation(parts), tld_boundary(parts))
+  end
+
+  @impl true
+  def classify(fqdn, {_subdomain, label, _suffix} = parts, _opts) do
+    inside =
+      case mutated_label(fqdn, parts) do
+        {:ok, new_label} ->
+          n = length(String.to_charlist(label))
+
+          for {i, ?-} <- insertions(label, new_label), i in 1..(n - 1)//1 do
+            %{position: i, original: "", replacement: "-"}
+          end
+
+        :error ->
+          []
+      end
+
+    boundary = for %{fqdn: ^fqdn, edit: edit} <- tld_boundary(parts), do: edit
+
+    inside ++ boundary
+  end
+
+  defp hyphenation({_subdomain, label, _suffix} = parts) do
+    for {i, before, c, rest} <- splits(label), i > 0 do
+      edit = %{position: i, original: "", replacement: "-"}
+      candidate(parts, before <> <<?-, c::utf8>> <> rest, kind(), edit)
+    end
+  end
+
+  defp tld_boundary({subdomain, label, suffix}) do
+    case String.split(suffix, ".", parts: 2) do
+      [first, rest] ->
+        edit = %{position: length(String.to_charlist(label)), original: ".", replacement: "-"}
+        [build({subdomain, "#{label}-#{first}", rest}, kind(), edit)]
+
+      [_] -> []
+    end
+  end
+end
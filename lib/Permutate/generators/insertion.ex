@@ -0,0 +1,54 @@
+defmodule DomainTwistex.Permutate.Generators.Insertion do
+  @moduledoc """
+  Inserts keys adjacent to label characters on the selected keyboard
+  layouts (see `DomainTwistex.Permutate.Keyboard`).
+  """
+
+  use DomainTwistex.Permutate.Generator
+
+  alias DomainTwistex.Permutate.Keyboard
+
+  @impl true
+  def kind, do: "Insertion"
+
+  @impl true
+  def validate_opts(opts), do: Keyboard.validate_opts(opts)
+
+  @impl true
+  def generate({_subdomain, label, _suffix} = parts, opts) do
+    cuts = splits(label)
+
+    # The key is inserted before position i, next to the character after it
+    for {{i, before, current, rest}, {_, _, c, _}} <- Enum.zip(cuts, Enum.drop(cuts, 1)),
+        {layout_name, _weight} <- Keyboard.layouts(opts),
+        keyboard_char <- Keyboard.neighbours(layout_name, c) do
+      edit = %{position: i, original: "", replacement: <<keyboard_char::utf8>>, layout: layout_name}
+      candidate(parts, before <> <<keyboard_char::utf8, current::utf8>> <> rest, kind(), edit)
+    end
+  end
+
+  @impl true
+  def classify(fqdn, {_subdomain, label, _suffix} = parts, opts) do
+    case mutated_label(fqdn, parts) do
+      {:ok, new_label} ->
+        chars = List.to_tuple(String.to_charlist(label))
+
+        for {i, inserted} <- insertions(label, new_label),
+            i <= tuple_size(chars) - 2,
+            layout_name = neighbour_layout(elem(chars, i + 1), inserted, opts),
+            layout_name != nil do
+          %{position: i, original: "", replacement: <<inserted::utf8>>, layout: layout_name}
+        end
+
+      :error ->
+        []
+    end
+  end
+
+  # First selected layout on which `key` neighbours `c`
+  defp neighbour_layout(c, key, opts) do
+    Enum.find_value(Keyboard.layouts(opts), fn {layout_name, _weight} ->
+      if Keyboard.neighbour?(layout_name, c, key), do: layout_name
+    end)
+  end
+end
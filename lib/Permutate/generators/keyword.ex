@@ -0,0 +1,145 @@
+defmodule DomainTwistex.Permutate.Generators.Keyword do
+  @moduledoc """
+  Prepends and appends phishing keywords (`example-login`, `loginexample`),
+  the combosquatting pattern.
+
+  Keywords come from `DomainTwistex.Permutate.Keywords` (the `:keywords`
+  option: a pack such as `:banking`, a file or a list), and each edit
+  carries the keyword's `:category`. Options:
+
+    * `:keyword_joins` - how the keyword is joined to the label (default:
+      `[:hyphen, :none]`):
+      * `:hyphen` - `example-login`
+      * `:none` - `examplelogin`
+      * `:dot` - `example.login`, the label as a subdomain of the keyword
+        domain (appended keywords only, as a prepended one would be a
+        subdomain of the original domain)
+      * `:underscore_to_hyphen` - like `:hyphen`, with the underscores of
+        multi-word keywords turned into hyphens (`example-customer-service`).
+        Other joins drop them (`examplecustomerservice`)
+    * `:keyword_placements` - `[:suffix, :prefix]` by default
+  """
+
+  use DomainTwistex.Permutate.Generator
+
+  alias DomainTwistex.Permutate.Keywords
+
+  @joins [hyphen: "-", none: "", dot: ".", underscore_to_hyphen: "-"]
+  @default_joins [:hyphen, :none]
+  @placements [:suffix, :prefix]
+
+  @impl true
+  def kind, do: "Keyword"
+
+  @impl true
+  def validate_opts(opts) do
+    with :ok <- Keywords.validate_opts(opts),
+         :ok <- validate_subset(opts, :keyword_joins, Keyword.keys(@joins)) do
+      validate_subset(opts, :keyword_placements, @placements)
+    end
+  end
+
+  @impl true
+  def generate({_subdomain, label, _suffix} = parts, opts) do
+    for {keyword, category} <- entries(opts),
+        placement <- placements(opts),
+        join <- joins(opts),
+        join != :dot or placement == :suffix,
+        kw = render(keyword, join),
+        valid_keyword?(kw) do
+      separator = Keyword.fetch!(@joins, join)
+      new_label = if placement == :suffix, do: label <> separator <> kw, else: kw <> separator <> label
+      candidate(parts, new_label, kind(), %{keyword: kw, placement: placement, separator: separator, category: category})
+    end
+  end
+
+  @impl true
+  def classify(fqdn, {_subdomain, label, _suffix} = parts, opts) do
+    case mutated_label(fqdn, parts) do
+      {:ok, new_label} ->
+        categories = Keyword.get_lazy(opts, :keyword_categories, fn -> categories(entries(opts), opts) end)
+
+        for placement <- placements(opts),
+            join <- joins(opts),
+            join != :dot or placement == :suffix,
+            separator = Keyword.fetch!(@joins, join),
+            {:ok, kw} <- [strip(new_label, label, separator, placement)],
+            {:ok, category} <- [Map.fetch(categories, {kw, join})] do
+          %{keyword: kw, placement: placement, separator: separator, category: category}
+        end
+        |> Enum.uniq()
+
+      :error ->
+        []
+    end
+  end
+
+  # Attribution classifies every candidate of a run, so the keyword list and
+  # its category map are resolved once per run
+  @impl true
+  def prepare_opts(opts) do
+    entries = Keywords.load(opts)
+    Keyword.merge(opts, keyword_entries: entries, keyword_categories: categories(entries, opts))
+  end
+
+  # A keyword is joined before or after the label
+  @impl true
+  def plausible?(fqdn, {_subdomain, label, _suffix} = parts, _opts) do
+    case mutated_label(fqdn, parts) do
+      {:ok, new_label} ->
+        byte_size(new_label) > byte_size(label) and
+          (String.starts_with?(new_label, label) or String.ends_with?(new_label, label))
+
+      :error ->
+        false
+    end
+  end
+
+  defp joins(opts), do: Keyword.get(opts, :keyword_joins, @default_joins)
+  defp placements(opts), do: Keyword.get(opts, :keyword_placements, @placements)
+
+  defp render(keyword, :underscore_to_hyphen), do: String.replace(keyword, "_", "-")
+  defp render(keyword, _join), do: String.replace(keyword, "_", "")
+
+  defp valid_keyword?(kw), do: String.match?(kw, ~r/^[\p{L}\p{N}]([\p{L}\p{N}-]*[\p{L}\p{N}])?$/u)
+
+  defp strip(new_label, label, separator, :suffix) do
+    case String.split_at(new_label, String.length(label <> separator)) do
+      {prefix, kw} when prefix == label <> separator and kw != "" -> {:ok, kw}
+      _ -> :error
+    end
+  end
+
+  defp strip(new_label, label, separator, :prefix) do
+    case String.split_at(new_label, -String.length(separator <> label)) do
+      {kw, suffix} when suffix == separator <> label and kw != "" -> {:ok, kw}
+      _ -> :error
+    end
+  end
+
+  # Loaded here when the generator is called outside `prepare_opts/1`
+  defp entries(opts), do: Keyword.get_lazy(opts, :keyword_entries, fn -> Keywords.load(opts) end)
+
+  # {rendered keyword, join} => category
+  defp categories(entries, opts) do
+    for {keyword, category} <- Enum.reverse(entries), join <- joins(opts), into: %{} do
+      {{render(keyword, join), join}, category}
+    end
+  end
+
+  defp validate_subset(opts, key, allowed) do
+    case Keyword.get(opts, key) do
+      nil ->
+        :ok
+
+      values when is_list(values) and values != [] ->
+        case values -- allowed do
+          [] -> :ok
+          invalid -> {:error, "#{inspect(key)} entries must be among #{inspect(allowed)}, got: #{inspect(invalid)}"}
+        end
+
+      other ->
+        {:error, "#{inspect(key)} must be a non-empty list, got: #{inspect(other)}"}
+    end
+  end
+end
@@ -0,0 +1,62 @@
+defmodule DomainTwistex.Permutate.Generators.LayoutSwitch do
+  @moduledoc """
+  Retypes the label with the wrong keyboard layout active, as users who
+  forget to switch layout do: a Latin label typed on a Russian, Greek or
+  Hebrew layout (`пщщпду` for `google`), and a label in one of those scripts
+  typed on a Latin layout (`ghbdtn` for `привет`).
+
+  Keys are matched by physical position with
+  `DomainTwistex.Permutate.Keyboard.transcode/3`, on each Latin layout
+  selected by the `:layouts` option (`:mobile` has no physical counterpart
+  and is skipped). Non-ASCII labels are emitted as IDNA punycode.
+  """
+
+  use DomainTwistex.Permutate.Generator
+
+  alias DomainTwistex.Permutate.Keyboard
+
+  @impl true
+  def kind, do: "LayoutSwitch"
+
+  @impl true
+  def validate_opts(opts), do: Keyboard.validate_opts(opts)
+
+  @impl true
+  def generate({_subdomain, label, _suffix} = parts, opts) do
+    for {new_label, edit} <- switches(label, opts) do
+      candidate(parts, new_label, kind(), edit)
+    end
+  end
+
+  @impl true
+  def classify(fqdn, {_subdomain, label, _suffix} = parts, opts) do
+    case mutated_label(fqdn, parts) do
+      {:ok, new_label} -> for {^new_label, edit} <- switches(label, opts), do: edit
+      :error -> []
+    end
+  end
+
+  # Keys are switched one for one
+  @impl true
+  def plausible?(fqdn, {_subdomain, label, _suffix} = parts, _opts) do
+    case mutated_label(fqdn, parts) do
+      {:ok, new_label} -> new_label != label and String.length(new_label) == String.length(label)
+      :error -> false
+    end
+  end
+
+  # {new_label, edit} for every Latin/script layout pair the label can be
+  # typed on, in both directions
+  defp switches(label, opts) do
+    for {latin, _weight} <- Keyboard.layouts(opts),
+        script <- Keyboard.script_layouts(),
+        {intended, active} <- [{latin, script}, {script, latin}],
+        Keyboard.typeable?(label, intended),
+        {:ok, new_label} <- [Keyboard.transcode(label, intended, active)],
+        new_label != label,
+        String.match?(new_label, ~r/^[\p{L}\p{N}-]+$/u) do
+      {new_label, %{original: label, replacement: new_label, intended_layout: intended, active_layout: active}}
+    end
+    |> Enum.uniq_by(&elem(&1, 0))
+  end
+end
@@ -0,0 +1,40 @@
+defmodule DomainTwistex.Permutate.Generators.Mapped do
+  @moduledoc """
+  Applies visual character substitutions (`l` -> `1`, `m` -> `rn`, ...).
+  """
+
+  use DomainTwistex.Permutate.Generator
+
+  alias DomainTwistex.Permutate.Data
+
+  @mapped Data.mapped()
+
+  @impl true
+  def kind, do: "Mapped"
+
+  @impl true
+  def generate({_subdomain, label, _suffix} = parts, _opts) do
+    for {key, values} <- @mapped,
+        String.contains?(label, key),
+        mapped_value <- values do
+      edit = %{original: key, replacement: mapped_value}
+      candidate(parts, String.replace(label, key, mapped_value), kind(), edit)
+    end
+  end
+
+  @impl true
+  def classify(fqdn, {_subdomain, label, _suffix} = parts, _opts) do
+    case mutated_label(fqdn, parts) do
+      {:ok, new_label} ->
+        for {key, values} <- @mapped,
+            String.contains?(label, key),
+            mapped_value <- values,
+            String.replace(label, key, mapped_value) == new_label do
+          %{original: key, replacement: mapped_value}
+        end
+
+      :error ->
+        []
+    end
+  end
+end
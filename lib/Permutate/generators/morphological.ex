@@ -0,0 +1,142 @@
+defmodule DomainTwistex.Permutate.Generators.Morphological do
+  @moduledoc """
+  Word-level changes that keep the brand readable: English plural and
+  singular forms (`northwinds` for `northwind`), number words swapped for
+  digits (`123` for `onetwothree`, `4-you` for `for-you`) and digits
+  spelled out (`b2b` to `btob`, `4you` to `foryou`).
+
+  Like Mapped, it applies substitution tables, `Data.inflections/0` and
+  `Data.number_words/0`, but only at word boundaries: the label's edges,
+  hyphens and changes between letters and digits. Number words must make
+  up a whole word, so `often` never becomes `of10`. Edits record the
+  `:rule` (`:plural`, `:singular`, `:word_to_digit` or `:digit_to_word`).
+  """
+
+  use DomainTwistex.Permutate.Generator
+
+  alias DomainTwistex.Permutate.Data
+
+  @inflections Data.inflections()
+  @number_words Data.number_words()
+
+  # Longest first, so that `too` is tried before `to`
+  @words_by_length Enum.sort_by(@number_words, &(-byte_size(elem(&1, 0))))
+
+  # Digits => their words, usual spelling first
+  @digit_words Enum.reduce(Enum.reverse(@number_words), %{}, fn {word, digit}, acc ->
+                 Map.update(acc, digit, [word], &[word | &1])
+               end)
+
+  @min_inflected_length 3
+
+  @impl true
+  def kind, do: "Morphological"
+
+  @impl true
+  def generate({_subdomain, label, _suffix} = parts, _opts) do
+    for {new_label, edit} <- morphs(label) do
+      candidate(parts, new_label, kind(), edit)
+    end
+  end
+
+  @impl true
+  def classify(fqdn, {_subdomain, label, _suffix} = parts, _opts) do
+    case mutated_label(fqdn, parts) do
+      {:ok, new_label} -> for {^new_label, edit} <- morphs(label), do: edit
+      :error -> []
+    end
+  end
+
+  defp morphs(label) do
+    letters = Regex.scan(~r/[a-z]+/, label, return: :index) |> Enum.map(&hd/1)
+    digits = Regex.scan(~r/[0-9]+/, label, return: :index) |> Enum.map(&hd/1)
+
+    (Enum.flat_map(letters, &inflections(label, &1)) ++
+       Enum.flat_map(letters, &words_to_digits(label, &1)) ++
+       Enum.flat_map(digits, &digits_to_words(label, &1)))
+    |> Enum.reject(&(elem(&1, 0) == label))
+    |> Enum.uniq_by(&elem(&1, 0))
+  end
+
+  # Plural or singular form of the word ending at the end of the run
+  defp inflections(label, {start, len}) when len >= @min_inflected_length do
+    word = binary_part(label, start, len)
+
+    case Enum.find(@inflections, fn {ending, _} -> String.ends_with?(word, ending) and len > byte_size(ending) end) do
+      {ending, forms} ->
+        for form <- forms do
+          rule = if byte_size(form) > byte_size(ending), do: :plural, else: :singular
+          change(label, start + len - byte_size(ending), byte_size(ending), form, rule)
+        end
+
+      nil ->
+        []
+    end
+  end
+
+  defp inflections(_label, _run), do: []
+
+  # Number words of a run made up of number words only, one at a time and
+  # all together
+  defp words_to_digits(label, {start, len}) do
+    case split_words(binary_part(label, start, len), start) do
+      nil ->
+        []
+
+      words ->
+        each = for {offset, word, digit} <- words, do: change(label, offset, byte_size(word), digit, :word_to_digit)
+
+        if length(words) > 1 do
+          each ++ [change(label, start, len, Enum.map_join(words, &elem(&1, 2)), :word_to_digit)]
+        else
+          each
+        end
+    end
+  end
+
+  # Each digit spelled out, the run spelled out digit by digit, and numbers
+  # with a word of their own (`10`)
+  defp digits_to_words(label, {start, len}) do
+    run = binary_part(label, start, len)
+    chars = for <<c <- run>>, do: <<c>>
+
+    each =
+      for {digit, i} <- Enum.with_index(chars),
+          word <- Map.get(@digit_words, digit, []) do
+        change(label, start + i, 1, word, :digit_to_word)
+      end
+
+    if len > 1 do
+      spelled = Enum.map_join(chars, &hd(Map.fetch!(@digit_words, &1)))
+      whole = for word <- Map.get(@digit_words, run, []), do: change(label, start, len, word, :digit_to_word)
+      each ++ [change(label, start, len, spelled, :digit_to_word) | whole]
+    else
+      each
+    end
+  end
+
+  # Splits a letter run entirely into number words, as {offset, word, digit}
+  # tuples, or returns nil
+  defp split_words("", _offset), do: []
+
+  defp split_words(rest, offset) do
+    Enum.find_value(@words_by_length, fn {word, digit} ->
+      size = byte_size(word)
+
+      with true <- String.starts_with?(rest, word),
+           tail when is_list(tail) <- split_words(binary_part(rest, size, byte_size(rest) - size), offset + size) do
+        [{offset, word, digit} | tail]
+      else
+        _ -> nil
+      end
+    end)
+  end
+
+  defp change(label, offset, size, replacement, rule) do
+    new_label = binary_part(label, 0, offset) <> replacement <> binary_part(label, offset + size, byte_size(label) - offset - size)
+    position = String.length(binary_part(label, 0, offset))
+    original = binary_part(label, offset, size)
+
+    {new_label, %{position: position, original: original, replacement: replacement, rule: rule}}
+  end
+end
@@ -0,0 +1,30 @@
+defmodule DomainTwistex.Permutate.Generators.Omission do
+  @moduledoc """
+  Removes each label character in turn (`exmple`).
+  """
+
+  use DomainTwistex.Permutate.Generator
+
+  @impl true
+  def kind, do: "Omission"
+
+  @impl true
+  def generate({_subdomain, label, _suffix} = parts, _opts) do
+    for {i, before, c, rest} <- splits(label), before <> rest != "" do
+      candidate(parts, before <> rest, kind(), %{position: i, original: <<c::utf8>>, replacement: ""})
+    end
+  end
+
+  @impl true
+  def classify(fqdn, {_subdomain, label, _suffix} = parts, _opts) do
+    case mutated_label(fqdn, parts) do
+      {:ok, new_label} ->
+        for {i, c} <- deletions(label, new_label) do
+          %{position: i, original: <<c::utf8>>, replacement: ""}
+        end
+
+      :error ->
+        []
+    end
+  end
+end
@@ -0,0 +1,80 @@
+defmodule DomainTwistex.Permutate.Generators.Phonetic do
+  @moduledoc """
+  Respells the label so that it sounds the same when spoken (`fone` for
+  `phone`, `kwik` for `quick`, `rite` for `right`), the squats that radio and
+  podcast ads invite and that keyboard models miss.
+
+  Up to two grapheme substitutions from `Data.phonetic_rules/0` are applied,
+  and only labels that share a Double Metaphone key with the original are
+  kept (see `DomainTwistex.Permutate.Phonetic`).
+  """
+
+  use DomainTwistex.Permutate.Generator
+
+  alias DomainTwistex.Permutate.Data
+  alias DomainTwistex.Permutate.Phonetic
+
+  @rules Data.phonetic_rules()
+
+  # Replacement => the spellings it can stand for, to undo a substitution
+  @inverse_rules Enum.reduce(@rules, %{}, fn {from, tos}, acc ->
+                   Enum.reduce(tos, acc, fn to, acc -> Map.update(acc, to, [from], &[from | &1]) end)
+                 end)
+
+  # Most a label's length can change with two substitutions
+  @max_length_change 2 * Enum.max(for {from, tos} <- @rules, to <- tos, do: abs(byte_size(from) - byte_size(to)))
+
+  @impl true
+  def kind, do: "Phonetic"
+
+  @impl true
+  def generate({_subdomain, label, _suffix} = parts, _opts) do
+    keys = Phonetic.keys(label)
+    once = substitutions(label, @rules)
+    twice = Enum.flat_map(once, &substitutions(&1, @rules))
+
+    for new_label <- Enum.uniq(once ++ twice),
+        new_label != label,
+        Enum.any?(Phonetic.keys(new_label), &(&1 in keys)) do
+      candidate(parts, new_label, kind(), %{original: label, replacement: new_label})
+    end
+  end
+
+  @impl true
+  def classify(fqdn, {_subdomain, label, _suffix} = parts, _opts) do
+    with {:ok, new_label} <- mutated_label(fqdn, parts),
+         true <- new_label != label,
+         true <- Phonetic.soundalike?(label, new_label),
+         true <- respelling?(label, new_label) do
+      [%{original: label, replacement: new_label}]
+    else
+      _ -> []
+    end
+  end
+
+  @impl true
+  def plausible?(fqdn, {_subdomain, label, _suffix} = parts, _opts) do
+    case mutated_label(fqdn, parts) do
+      {:ok, new_label} -> abs(byte_size(new_label) - byte_size(label)) <= @max_length_change
+      :error -> false
+    end
+  end
+
+  # Whether at most two substitutions turn `label` into `new_label`: meets in
+  # the middle instead of enumerating every two-step respelling
+  defp respelling?(label, new_label) do
+    once = MapSet.new(substitutions(label, @rules))
+
+    MapSet.member?(once, new_label) or
+      Enum.any?(substitutions(new_label, @inverse_rules), &MapSet.member?(once, &1))
+  end
+
+  # Every label obtained by applying one rule at one position
+  defp substitutions(label, rules) do
+    for {from, tos} <- rules,
+        {pos, len} <- :binary.matches(label, from),
+        to <- tos do
+      binary_part(label, 0, pos) <> to <> binary_part(label, pos + len, byte_size(label) - pos - len)
+    end
+  end
+end
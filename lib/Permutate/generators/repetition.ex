@@ -0,0 +1,37 @@
+defmodule DomainTwistex.Permutate.Generators.Repetition do
+  @moduledoc """
+  Doubles each alphabetic label character (`exxample`).
+  """
+
+  use DomainTwistex.Permutate.Generator
+
+  @impl true
+  def kind, do: "Repetition"
+
+  @impl true
+  def generate({_subdomain, label, _suffix} = parts, _opts) do
+    for {i, before, c, rest} <- splits(label),
+        c >= ?a and c <= ?z or c >= ?A and c <= ?Z do
+      edit = %{position: i + 1, original: "", replacement: <<c>>}
+      candidate(parts, before <> <<c, c>> <> rest, kind(), edit)
+    end
+  end
+
+  @impl true
+  def classify(fqdn, {_subdomain, label, _suffix} = parts, _opts) do
+    case mutated_label(fqdn, parts) do
+      {:ok, new_label} ->
+        chars = List.to_tuple(String.to_charlist(label))
+
+        # The repeated character follows its original, at i + 1
+        for {i, c} <- insertions(label, new_label),
+            i >= 1 and elem(chars, i - 1) == c,
+            c >= ?a and c <= ?z or c >= ?A and c <= ?Z do
+          %{position: i, original: "", replacement: <<c>>}
+        end
+
+      :error ->
+        []
+    end
+  end
+end
@@ -0,0 +1,44 @@
+defmodule DomainTwistex.Permutate.Generators.Replacement do
+  @moduledoc """
+  Replaces label characters with adjacent keys on the selected keyboard
+  layouts (see `DomainTwistex.Permutate.Keyboard`).
+  """
+
+  use DomainTwistex.Permutate.Generator
+
+  alias DomainTwistex.Permutate.Keyboard
+
+  @impl true
+  def kind, do: "Replacement"
+
+  @impl true
+  def validate_opts(opts), do: Keyboard.validate_opts(opts)
+
+  @impl true
+  def generate({_subdomain, label, _suffix} = parts, opts) do
+    for {i, before, c, rest} <- splits(label),
+        {layout_name, _weight} <- Keyboard.layouts(opts),
+        keyboard_char <- Keyboard.neighbours(layout_name, c) do
+      edit = %{position: i, original: <<c::utf8>>, replacement: <<keyboard_char::utf8>>, layout: layout_name}
+      candidate(parts, before <> <<keyboard_char::utf8>> <> rest, kind(), edit)
+    end
+  end
+
+  @impl true
+  def classify(fqdn, {_subdomain, label, _suffix} = parts, opts) do
+    with {:ok, new_label} <- mutated_label(fqdn, parts),
+         {i, [c], [keyboard_char]} <- difference(label, new_label),
+         layout_name when layout_name != nil <- neighbour_layout(c, keyboard_char, opts) do
+      [%{position: i, original: <<c::utf8>>, replacement: <<keyboard_char::utf8>>, layout: layout_name}]
+    else
+      _ -> []
+    end
+  end
+
+  # First selected layout on which `key` neighbours `c`
+  defp neighbour_layout(c, key, opts) do
+    Enum.find_value(Keyboard.layouts(opts), fn {layout_name, _weight} ->
+      if Keyboard.neighbour?(layout_name, c, key), do: layout_name
+    end)
+  end
+end
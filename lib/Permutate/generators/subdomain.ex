@@ -0,0 +1,38 @@
+defmodule DomainTwistex.Permutate.Generators.Subdomain do
+  @moduledoc """
+  Inserts a dot inside the label, turning its head into a subdomain
+  (`exa.mple.com`).
+  """
+
+  use DomainTwistex.Permutate.Generator
+
+  @impl true
+  def kind, do: "Subdomain"
+
+  @impl true
+  def generate({_subdomain, label, _suffix} = parts, _opts) do
+    cuts = splits(label)
+
+    for {{i, before, c1, _}, {_, _, c2, rest}} <- Enum.zip(cuts, Enum.drop(cuts, 1)), c1 != ?- and c2 != ?- do
+      edit = %{position: i + 1, original: "", replacement: "."}
+      candidate(parts, before <> <<c1::utf8, ?., c2::utf8>> <> rest, kind(), edit)
+    end
+  end
+
+  @impl true
+  def classify(fqdn, {_subdomain, label, _suffix} = parts, _opts) do
+    case mutated_label(fqdn, parts) do
+      {:ok, new_label} ->
+        chars = List.to_tuple(String.to_charlist(label))
+
+        for {i, ?.} <- insertions(label, new_label),
+            i in 1..(tuple_size(chars) - 1)//1,
+            elem(chars, i - 1) != ?- and elem(chars, i) != ?- do
+          %{position: i, original: "", replacement: "."}
+        end
+
+      :error ->
+        []
+    end
+  end
+end
@@ -0,0 +1,32 @@
+defmodule DomainTwistex.Permutate.Generators.Tld do
+  @moduledoc """
+  Swaps the public suffix for every known TLD (`example.net`), from
+  `DomainTwistex.Permutate.Data.tlds/0`.
+  """
+
+  use DomainTwistex.Permutate.Generator
+
+  alias DomainTwistex.Permutate.Data
+
+  @impl true
+  def kind, do: "Tld"
+
+  @impl true
+  def generate({subdomain, label, suffix}, _opts) do
+    Stream.map(Data.tlds(), fn tld ->
+      build({subdomain, label, tld}, kind(), %{original: suffix, replacement: tld})
+    end)
+  end
+
+  @impl true
+  def classify(fqdn, {subdomain, label, suffix}, _opts) do
+    prefix = if subdomain == "", do: label <> ".", else: "#{subdomain}.#{label}."
+    tld = String.replace_prefix(fqdn, prefix, "")
+
+    if String.starts_with?(fqdn, prefix) and tld != suffix and MapSet.member?(Data.tld_set(), tld) do
+      [%{original: suffix, replacement: tld}]
+    else
+      []
+    end
+  end
+end
@@ -0,0 +1,104 @@
+defmodule DomainTwistex.Permutate.Generators.TldTypo do
+  @moduledoc """
+  Typos of the TLD itself, a small and high-signal alternative to swapping
+  in every known TLD (Tld):
+
+    * omission (`.cm`, `.om`) and transposition (`.ocm`) of its letters
+    * substitution with a neighbouring key on the selected `:layouts` (`.xom`)
+    * known confusable suffixes, from `Data.confusable_tlds/0` (`.co` for
+      `.com`, `.ne` for `.net`, `.orq` for `.org`)
+    * the dot before the suffix dropped or typed as a hyphen, with another
+      popular TLD appended (`examplecom.net`, `example-com.org`)
+
+  For multi-label suffixes (`co.uk`) the keyboard typos apply to the last
+  label. Edits record the `:typo` made; dot edits also carry the new
+  `:suffix`.
+  """
+
+  use DomainTwistex.Permutate.Generator
+
+  alias DomainTwistex.Permutate.Data
+  alias DomainTwistex.Permutate.Keyboard
+
+  @confusables Enum.reduce(Data.confusable_tlds(), %{}, fn {a, b}, acc ->
+                 acc |> Map.update(a, [b], &(&1 ++ [b])) |> Map.update(b, [a], &(&1 ++ [a]))
+               end)
+  @popular_tlds Data.popular_tlds()
+
+  @impl true
+  def kind, do: "TldTypo"
+
+  @impl true
+  def validate_opts(opts), do: Keyboard.validate_opts(opts)
+
+  @impl true
+  def generate({subdomain, label, suffix} = parts, opts) do
+    typos =
+      for {new_suffix, typo} <- suffix_typos(suffix, opts) do
+        build({subdomain, label, new_suffix}, kind(), %{original: suffix, replacement: new_suffix, typo: typo})
+      end
+
+    typos ++ dot_typos(parts)
+  end
+
+  @impl true
+  def classify(fqdn, parts, opts) do
+    for %{fqdn: ^fqdn, edit: edit} <- generate(parts, opts), do: edit
+  end
+
+  # Every typo keeps everything up to the end of the label
+  @impl true
+  def plausible?(fqdn, {subdomain, label, _suffix}, _opts) do
+    prefix = if subdomain == "", do: label, else: "#{subdomain}.#{label}"
+    String.starts_with?(fqdn, prefix)
+  end
+
+  # {new_suffix, typo} for the keyboard typos and confusables of `suffix`
+  defp suffix_typos(suffix, opts) do
+    {head, tld} =
+      case suffix |> String.split(".") |> Enum.split(-1) do
+        {[], [tld]} -> {"", tld}
+        {labels, [tld]} -> {Enum.join(labels, ".") <> ".", tld}
+      end
+
+    chars = String.to_charlist(tld)
+    n = length(chars)
+
+    omissions = for i <- 0..(n - 1)//1, do: {List.delete_at(chars, i), :omission}
+
+    transpositions =
+      for i <- 0..(n - 2)//1, Enum.at(chars, i) != Enum.at(chars, i + 1) do
+        {chars |> List.replace_at(i, Enum.at(chars, i + 1)) |> List.replace_at(i + 1, Enum.at(chars, i)), :transposition}
+      end
+
+    substitutions =
+      for {c, i} <- Enum.with_index(chars),
+          {layout, _weight} <- Keyboard.layouts(opts),
+          key <- Keyboard.neighbours(layout, c),
+          key in ?a..?z do
+        {List.replace_at(chars, i, key), :substitution}
+      end
+
+    keyboard =
+      for {new_chars, typo} <- omissions ++ transpositions ++ substitutions, length(new_chars) >= 2 do
+        {head <> List.to_string(new_chars), typo}
+      end
+
+    confusables = for other <- Map.get(@confusables, suffix, []), do: {other, :confusable}
+
+    (confusables ++ keyboard)
+    |> Enum.reject(&(elem(&1, 0) == suffix))
+    |> Enum.uniq_by(&elem(&1, 0))
+  end
+
+  # The suffix folded into the label, moved to another TLD; folds that keep
+  # the suffix are Various candidates
+  defp dot_typos({subdomain, label, suffix} = parts) do
+    position = length(String.to_charlist(label))
+
+    for {new_label, separator} <- fold_suffix(parts), tld <- @popular_tlds, tld != suffix do
+      edit = %{position: position, original: ".", replacement: separator, typo: :dot, suffix: tld}
+      build({subdomain, new_label, tld}, kind(), edit)
+    end
+  end
+end
@@ -0,0 +1,30 @@
+defmodule DomainTwistex.Permutate.Generators.Transposition do
+  @moduledoc """
+  Swaps adjacent label characters (`exmaple`).
+  """
+
+  use DomainTwistex.Permutate.Generator
+
+  @impl true
+  def kind, do: "Transposition"
+
+  @impl true
+  def generate({_subdomain, label, _suffix} = parts, _opts) do
+    cuts = splits(label)
+
+    for {{i, before, c1, _}, {_, _, c2, rest}} <- Enum.zip(cuts, Enum.drop(cuts, 1)), c1 != c2 do
+      edit = %{position: i, original: <<c1::utf8, c2::utf8>>, replacement: <<c2::utf8, c1::utf8>>}
+      candidate(parts, before <> <<c2::utf8, c1::utf8>> <> rest, kind(), edit)
+    end
+  end
+
+  @impl true
+  def classify(fqdn, {_subdomain, label, _suffix} = parts, _opts) do
+    with {:ok, new_label} <- mutated_label(fqdn, parts),
+         {i, [c1, c2], [c2, c1]} <- difference(label, new_label) do
+      [%{position: i, original: <<c1::utf8, c2::utf8>>, replacement: <<c2::utf8, c1::utf8>>}]
+    else
+      _ -> []
+    end
+  end
+end
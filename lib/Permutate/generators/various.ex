@@ -0,0 +1,78 @@
+defmodule DomainTwistex.Permutate.Generators.Various do
+  @moduledoc """
+  Dropped dots and service prefixes, the typos real traffic lands on:
+
+    * a service prefix run into the label, with or without a hyphen
+      (`wwwexample.com`, `www-example.com`, `mail-example.com`)
+    * the suffix run into the label (`examplecom.com`, `example-com.com`)
+    * the last subdomain label run into the label (`loginexample.com` for
+      `login.example.com`)
+
+  Prefixes default to `www`, `mail`, `webmail`, `m` and `login`, and are set
+  with the `:service_prefixes` option. Edits record the `:pattern`
+  (`:service_prefix`, `:suffix` or `:subdomain`).
+  """
+
+  use DomainTwistex.Permutate.Generator
+
+  @default_prefixes ~w(www mail webmail m login)
+
+  @impl true
+  def kind, do: "Various"
+
+  @impl true
+  def validate_opts(opts) do
+    prefixes = Keyword.get(opts, :service_prefixes, @default_prefixes)
+
+    if is_list(prefixes) and Enum.all?(prefixes, &(is_binary(&1) and &1 != "")) do
+      :ok
+    else
+      {:error, ":service_prefixes must be a list of non-empty strings, got: #{inspect(prefixes)}"}
+    end
+  end
+
+  @impl true
+  def generate(parts, opts) do
+    service_prefixes(parts, opts) ++ suffix_folds(parts) ++ subdomain_folds(parts)
+  end
+
+  @impl true
+  def classify(fqdn, parts, opts) do
+    for %{fqdn: ^fqdn, edit: edit} <- generate(parts, opts), do: edit
+  end
+
+  # Every pattern keeps the suffix and the label's letters
+  @impl true
+  def plausible?(fqdn, {_subdomain, label, suffix}, _opts) do
+    String.ends_with?(fqdn, "." <> suffix) and String.contains?(fqdn, label)
+  end
+
+  defp service_prefixes({_subdomain, label, _suffix} = parts, opts) do
+    for prefix <- Keyword.get(opts, :service_prefixes, @default_prefixes),
+        prefix = String.downcase(prefix),
+        separator <- ["", "-"] do
+      edit = %{position: 0, original: "", replacement: prefix <> separator, pattern: :service_prefix}
+      candidate(parts, prefix <> separator <> label, kind(), edit)
+    end
+  end
+
+  defp suffix_folds({_subdomain, label, _suffix} = parts) do
+    position = length(String.to_charlist(label))
+
+    for {new_label, separator} <- fold_suffix(parts) do
+      edit = %{position: position, original: ".", replacement: separator, pattern: :suffix}
+      candidate(parts, new_label, kind(), edit)
+    end
+  end
+
+  defp subdomain_folds({"", _label, _suffix}), do: []
+
+  defp subdomain_folds({subdomain, label, suffix}) do
+    {labels, [last]} = subdomain |> String.split(".") |> Enum.split(-1)
+
+    for separator <- ["", "-"] do
+      edit = %{position: 0, original: "", replacement: last <> separator, pattern: :subdomain}
+      build({Enum.join(labels, "."), last <> separator <> label, suffix}, kind(), edit)
+    end
+  end
+end
@@ -0,0 +1,155 @@
+defmodule DomainTwistex.Permutate.Generators.VowelShuffle do
+  @moduledoc """
+  Replaces the label's vowels with every combination of vowels, up to
+  the first six vowel positions (`exomplu`).
+
+  Shuffling is case-insensitive, as DNS is: only lowercase vowels are put
+  in, so a label with `n` shuffled vowels has `5^n - 1` variants (up to
+  15,624). When that exceeds `:vowel_shuffle_budget` (default: 1000), a
+  sample of that size is emitted instead, in product order. The sample is
+  deterministic: it is seeded by the label and `:vowel_shuffle_seed`
+  (default: 0), so repeated runs check the same names. `skipped/2` reports
+  how many variants were left out.
+
+  Disabled with `vowel_shuffle: false`.
+  """
+
+  use DomainTwistex.Permutate.Generator
+
+  alias DomainTwistex.Permutate.Data
+  alias DomainTwistex.Permutate.Generator
+
+  @vowels Enum.filter(Data.vowels(), &(&1 in ?a..?z))
+  @vowel_tuple List.to_tuple(@vowels)
+  @vowel_shuffle_ceiling Data.vowel_shuffle_ceiling()
+  @default_budget 1000
+  @default_seed 0
+
+  @impl true
+  def kind, do: "VowelShuffle"
+
+  @impl true
+  def enabled?(opts), do: Keyword.get(opts, :vowel_shuffle, true)
+
+  @impl true
+  def validate_opts(opts) do
+    with :ok <- Generator.validate_boolean(opts, :vowel_shuffle) do
+      case {budget(opts), Keyword.get(opts, :vowel_shuffle_seed, @default_seed)} do
+        {budget, _seed} when not is_integer(budget) or budget < 0 ->
+          {:error, ":vowel_shuffle_budget must be a non-negative integer, got: #{inspect(budget)}"}
+
+        {_budget, seed} when not is_integer(seed) ->
+          {:error, ":vowel_shuffle_seed must be an integer, got: #{inspect(seed)}"}
+
+        _ ->
+          :ok
+      end
+    end
+  end
+
+  @impl true
+  def generate({_subdomain, label, _suffix} = parts, opts) do
+    case shuffled_vowels(label) do
+      [] ->
+        []
+
+      vowels ->
+        positions = Enum.map(vowels, &elem(&1, 0))
+        originals = Enum.map(vowels, &char_lower(elem(&1, 2)))
+        [head | tail] = segments(vowels)
+
+        # Each product is spliced between the fixed segments of the label
+        for index <- indexes(label, originals, opts) do
+          replacement = product(index, length(vowels))
+          new_label = IO.iodata_to_binary([head | Enum.zip_with(replacement, tail, &[&1, &2])])
+          changed = for {position, old, new} <- Enum.zip([positions, originals, replacement]), old != new, do: position
+          candidate(parts, new_label, kind(), %{positions: changed, original: label, replacement: new_label})
+        end
+    end
+  end
+
+  @impl true
+  def classify(fqdn, {_subdomain, label, _suffix} = parts, _opts) do
+    with {:ok, new_label} <- mutated_label(fqdn, parts),
+         chars = String.to_charlist(label),
+         new_chars = String.to_charlist(new_label),
+         true <- length(chars) == length(new_chars) do
+      shuffled = label |> shuffled_vowels() |> Enum.map(&elem(&1, 0))
+      changed = for {{c, n}, i} <- Enum.with_index(Enum.zip(chars, new_chars)), char_lower(c) != n, do: {i, n}
+
+      if changed != [] and Enum.all?(changed, fn {i, n} -> i in shuffled and n in @vowels end) do
+        [%{positions: Enum.map(changed, &elem(&1, 0)), original: label, replacement: new_label}]
+      else
+        []
+      end
+    else
+      _ -> []
+    end
+  end
+
+  @impl true
+  def skipped({_subdomain, label, _suffix}, opts) do
+    case length(shuffled_vowels(label)) do
+      0 -> 0
+      n -> max(Integer.pow(length(@vowels), n) - 1 - budget(opts), 0)
+    end
+  end
+
+  defp budget(opts), do: Keyword.get(opts, :vowel_shuffle_budget, @default_budget)
+
+  defp shuffled_vowels(label) do
+    label
+    |> splits()
+    |> Enum.filter(&(char_lower(elem(&1, 2)) in @vowels))
+    |> Enum.take(@vowel_shuffle_ceiling)
+  end
+
+  # Indexes into the product of vowels (first position most significant),
+  # other than the label's own: all of them, or a seeded sample of the
+  # budget's size, in order
+  defp indexes(label, originals, opts) do
+    n = length(originals)
+    total = Integer.pow(length(@vowels), n)
+    own = originals |> Enum.map(fn c -> Enum.find_index(@vowels, &(&1 == c)) end) |> Integer.undigits(length(@vowels))
+
+    if total - 1 <= budget(opts) do
+      Enum.reject(0..(total - 1), &(&1 == own))
+    else
+      seed = {Keyword.get(opts, :vowel_shuffle_seed, @default_seed), :erlang.phash2(label), n}
+      :exsss |> :rand.seed_s(seed) |> sample(%{}, budget(opts), total, own) |> Enum.sort()
+    end
+  end
+
+  defp sample(_state, picked, budget, _total, _own) when map_size(picked) >= budget, do: Map.keys(picked)
+
+  defp sample(state, picked, budget, total, own) do
+    {index, state} = :rand.uniform_s(total, state)
+
+    case index - 1 do
+      ^own -> sample(state, picked, budget, total, own)
+      index -> sample(state, Map.put(picked, index, true), budget, total, own)
+    end
+  end
+
+  defp product(index, n) do
+    digits = Integer.digits(index, length(@vowels))
+
+    for digit <- List.duplicate(0, n - length(digits)) ++ digits, do: elem(@vowel_tuple, digit)
+  end
+
+  # The label cut around the shuffled vowels: the text before the first
+  # vowel, between consecutive ones, and after the last
+  defp segments(vowels) do
+    {_index, _before, _c, rest} = List.last(vowels)
+
+    {inner, _offset} =
+      Enum.map_reduce(vowels, 0, fn {_index, before, _c, _rest}, offset ->
+        {binary_part(before, offset, byte_size(before) - offset), byte_size(before) + 1}
+      end)
+
+    inner ++ [rest]
+  end
+
+  defp char_lower(c) when c >= ?A and c <= ?Z, do: c + 32
+  defp char_lower(c), do: c
+end
@@ -0,0 +1,39 @@
+defmodule DomainTwistex.Permutate.Generators.VowelSwap do
+  @moduledoc """
+  Replaces each vowel in the label with every other vowel (`exomple`).
+  """
+
+  use DomainTwistex.Permutate.Generator
+
+  alias DomainTwistex.Permutate.Data
+
+  @vowels Data.vowels()
+
+  @impl true
+  def kind, do: "VowelSwap"
+
+  @impl true
+  def generate({_subdomain, label, _suffix} = parts, _opts) do
+    for {i, before, c, rest} <- splits(label),
+        char_lower(c) in @vowels,
+        vowel <- @vowels,
+        vowel != c do
+      edit = %{position: i, original: <<c>>, replacement: <<vowel>>}
+      candidate(parts, before <> <<vowel>> <> rest, kind(), edit)
+    end
+  end
+
+  @impl true
+  def classify(fqdn, {_subdomain, label, _suffix} = parts, _opts) do
+    with {:ok, new_label} <- mutated_label(fqdn, parts),
+         {i, [c], [vowel]} <- difference(label, new_label),
+         true <- char_lower(c) in @vowels and vowel in @vowels do
+      [%{position: i, original: <<c>>, replacement: <<vowel>>}]
+    else
+      _ -> []
+    end
+  end
+
+  defp char_lower(c) when c >= ?A and c <= ?Z, do: c + 32
+  defp char_lower(c), do: c
+end
@@ -0,0 +1,140 @@
+defmodule DomainTwistex.Permutate.Hostname do
+  @moduledoc """
+  Strict DNS host name validation (RFC 1035, RFC 1123 and RFC 5891).
+
+  Every generated candidate is checked here before it is returned, so that
+  names no registry or resolver would accept never cost a DNS query.
+
+  A name is rejected, with the first reason found, when:
+
+    * it is empty (`:empty`) or longer than 253 octets in ASCII form
+      (`:domain_too_long`)
+    * a label is empty, from a leading, trailing or doubled dot
+      (`:empty_label`)
+    * a label is longer than 63 octets (`:label_too_long`)
+    * a label has uppercase letters (`:uppercase`). Names are compared
+      lowercase, so these only duplicate the lowercase name
+    * a label has a character other than `a-z`, `0-9` and the hyphen, such
+      as an underscore (`{:invalid_character, char}`)
+    * a label starts or ends with a hyphen (`:hyphen_start_end`)
+    * a label has `--` in positions 3-4, which are reserved for IDNA, and is
+      not an `xn--` label (`:hyphen_3_4`)
+    * an `xn--` label is not a valid A-label. The reason is the one
+      `DomainTwistex.Permutate.IDNA` gives, such as `:invalid_punycode`
+    * the TLD is all digits (`:numeric_tld`)
+
+  Hyphens elsewhere are allowed, so `ex--ample.com` and `xn--a--yka.com`
+  (`a-ü.com`) are valid. Names with non-ASCII characters are checked in
+  their ASCII form, after `DomainTwistex.Permutate.IDNA.to_ascii/1`.
+  """
+
+  alias DomainTwistex.Permutate.IDNA
+
+  @ace_prefix "xn--"
+  @max_label_length 63
+  @max_domain_length 253
+
+  @type reason ::
+          :empty
+          | :domain_too_long
+          | :empty_label
+          | :label_too_long
+          | :uppercase
+          | {:invalid_character, String.t()}
+          | :hyphen_start_end
+          | :hyphen_3_4
+          | :numeric_tld
+          | IDNA.error_reason()
+
+  @doc """
+  Validates a host name.
+
+  ## Returns
+    * `:ok` - the name can be resolved as is
+    * `{:error, {reason, subject}}` - why not, with the offending label (or
+      the whole name, for `:empty`, `:domain_too_long` and Unicode names
+      with uppercase letters)
+
+  ## Examples
+
+      iex> DomainTwistex.Permutate.Hostname.validate("xn--a--yka.com")
+      :ok
+
+      iex> DomainTwistex.Permutate.Hostname.validate("my_site.com")
+      {:error, {{:invalid_character, "_"}, "my_site"}}
+
+      iex> DomainTwistex.Permutate.Hostname.validate("ab--cd.com")
+      {:error, {:hyphen_3_4, "ab--cd"}}
+  """
+  @spec validate(String.t()) :: :ok | {:error, {reason, String.t()}}
+  def validate(""), do: {:error, {:empty, ""}}
+
+  def validate(name) when is_binary(name) do
+    with {:ok, ascii} <- ascii_form(name),
+         :ok <- check_length(ascii),
+         labels = String.split(ascii, "."),
+         :ok <- check_labels(labels) do
+      check_tld(List.last(labels))
+    end
+  end
+
+  @doc """
+  Returns true when `validate/1` accepts the name.
+  """
+  @spec valid?(String.t()) :: boolean()
+  def valid?(name), do: validate(name) == :ok
+
+  defp ascii_form(name) do
+    cond do
+      IDNA.ascii?(name) -> {:ok, name}
+      String.downcase(name) != name -> {:error, {:uppercase, name}}
+      # IDNA drops the root dot, which candidates must not have either
+      String.ends_with?(name, ".") -> {:error, {:empty_label, ""}}
+      true -> IDNA.to_ascii(name)
+    end
+  end
+
+  defp check_length(ascii) do
+    if byte_size(ascii) > @max_domain_length,
+      do: {:error, {:domain_too_long, ascii}},
+      else: :ok
+  end
+
+  defp check_labels([]), do: :ok
+
+  defp check_labels([label | rest]) do
+    with :ok <- check_label(label), do: check_labels(rest)
+  end
+
+  defp check_label(""), do: {:error, {:empty_label, ""}}
+  defp check_label(label) when byte_size(label) > @max_label_length, do: {:error, {:label_too_long, label}}
+
+  defp check_label(label) do
+    cond do
+      reason = invalid_character(label) -> {:error, {reason, label}}
+      String.starts_with?(label, "-") or String.ends_with?(label, "-") -> {:error, {:hyphen_start_end, label}}
+      String.starts_with?(label, @ace_prefix) -> check_a_label(label)
+      match?(<<_, _, "--", _::binary>>, label) -> {:error, {:hyphen_3_4, label}}
+      true -> :ok
+    end
+  end
+
+  # The first character outside lowercase LDH, as a reason
+  defp invalid_character(<<c, rest::binary>>) when c in ?a..?z or c in ?0..?9 or c == ?-, do: invalid_character(rest)
+  defp invalid_character(<<c, _rest::binary>>) when c in ?A..?Z, do: :uppercase
+  defp invalid_character(<<c, _rest::binary>>), do: {:invalid_character, <<c>>}
+  defp invalid_character(<<>>), do: nil
+
+  defp check_a_label(label) do
+    case IDNA.to_unicode(label) do
+      {:ok, _unicode} -> :ok
+      {:error, {reason, _label}} -> {:error, {reason, label}}
+    end
+  end
+
+  defp check_tld(tld) do
+    if tld =~ ~r/\A[0-9]+\z/,
+      do: {:error, {:numeric_tld, tld}},
+      else: :ok
+  end
+end
@@ -0,0 +1,390 @@
+defmodule DomainTwistex.Permutate.IDNA do
+  @moduledoc """
+  Pure Elixir IDNA 2008 / UTS #46 encoder and decoder.
+
+  Converts internationalized domain names between their Unicode display form
+  (`ğöögle.com`) and the ASCII-compatible `xn--` form (`xn--gle-rnaa8v.com`)
+  that resolvers, WHOIS and RDAP servers expect.
+
+  Processing follows UTS #46 non-transitional processing:
+
+    1. Mapping - compatibility normalization (NFKC), lowercasing and
+       ideographic full stops mapped to `.`
+    2. Normalization - NFC
+    3. Validation of every label (see below)
+    4. Punycode encoding (RFC 3492) of every non-ASCII label
+
+  A label is rejected when:
+
+    * it is empty or longer than 63 octets in ASCII form
+    * it starts or ends with a hyphen, or has `--` in positions 3-4
+      without being a valid `xn--` label
+    * it starts with a combining mark
+    * it contains a code point outside the IDNA 2008 PVALID letter, mark and
+      digit categories (Ll, Lo, Lm, Mn, Mc, Nd) or the hyphen
+    * it contains a joiner (U+200C/U+200D)
+    * it mixes right-to-left scripts with left-to-right letters, or a
+      right-to-left label does not start with a right-to-left character
+      (a simplified form of the RFC 5893 Bidi rule)
+
+  The whole name must not exceed 253 octets in ASCII form.
+  """
+
+  @base 36
+  @tmin 1
+  @tmax 26
+  @skew 38
+  @damp 700
+  @initial_bias 72
+  @initial_n 128
+
+  @ace_prefix "xn--"
+  @max_label_length 63
+  @max_domain_length 253
+
+  @valid_char ~r/\A[\p{Ll}\p{Lo}\p{Lm}\p{Mn}\p{Mc}\p{Nd}\-]\z/u
+  @leading_mark ~r/\A\p{M}/u
+  @rtl_char ~r/[\p{Hebrew}\p{Arabic}\p{Syriac}\p{Thaana}\p{Nko}]/u
+  @rtl_start ~r/\A[\p{Hebrew}\p{Arabic}\p{Syriac}\p{Thaana}\p{Nko}]/u
+  @ltr_letter ~r/[^\P{L}\p{Hebrew}\p{Arabic}\p{Syriac}\p{Thaana}\p{Nko}]/u
+
+  @type error_reason ::
+          :invalid_utf8
+          | :empty_label
+          | :label_too_long
+          | :domain_too_long
+          | :hyphen_start_end
+          | :hyphen_3_4
+          | :leading_combining_mark
+          | :contextj
+          | :bidi
+          | :invalid_punycode
+          | :not_nfc
+          | {:disallowed, String.t()}
+
+  @doc """
+  Converts a domain to its ASCII-compatible form.
+
+  ## Returns
+    * `{:ok, ascii}` - the mapped and punycode-encoded domain
+    * `{:error, {reason, label}}` - the first label that failed validation
+
+  ## Examples
+
+      iex> DomainTwistex.Permutate.IDNA.to_ascii("ğöögle.com")
+      {:ok, "xn--gle-rnaa8v.com"}
+
+      iex> DomainTwistex.Permutate.IDNA.to_ascii("Bücher.de")
+      {:ok, "xn--bcher-kva.de"}
+
+      iex> DomainTwistex.Permutate.IDNA.to_ascii("-bücher.de")
+      {:error, {:hyphen_start_end, "-bücher"}}
+  """
+  @spec to_ascii(String.t()) :: {:ok, String.t()} | {:error, {error_reason, String.t()}}
+  def to_ascii(domain) do
+    with {:ok, labels} <- map_labels(domain),
+         {:ok, ascii_labels} <- map_while_ok(labels, &label_to_ascii/1) do
+      ascii = Enum.join(ascii_labels, ".")
+
+      if byte_size(ascii) > @max_domain_length do
+        {:error, {:domain_too_long, ascii}}
+      else
+        {:ok, ascii}
+      end
+    end
+  end
+
+  @doc """
+  Converts a domain to its Unicode display form, decoding `xn--` labels.
+
+  ## Examples
+
+      iex> DomainTwistex.Permutate.IDNA.to_unicode("xn--gle-rnaa8v.com")
+      {:ok, "ğöögle.com"}
+  """
+  @spec to_unicode(String.t()) :: {:ok, String.t()} | {:error, {error_reason, String.t()}}
+  def to_unicode(domain) do
+    with {:ok, labels} <- map_labels(domain),
+         {:ok, unicode_labels} <- map_while_ok(labels, &label_to_unicode/1) do
+      {:ok, Enum.join(unicode_labels, ".")}
+    end
+  end
+
+  @doc """
+  Returns true when the domain contains only ASCII characters.
+  """
+  def ascii?(<<c, rest::binary>>) when c < 0x80, do: ascii?(rest)
+  def ascii?(<<>>), do: true
+  def ascii?(_domain), do: false
+
+  # =============================================================================
+  # UTS #46 mapping and label processing
+  # =============================================================================
+
+  defp map_labels(domain) do
+    if String.valid?(domain) do
+      labels =
+        domain
+        |> :unicode.characters_to_nfkc_binary()
+        |> String.downcase()
+        |> String.replace(["。", "．", "｡"], ".")
+        |> :unicode.characters_to_nfc_binary()
+        |> String.trim_trailing(".")
+        |> String.split(".")
+
+      {:ok, labels}
+    else
+      {:error, {:invalid_utf8, domain}}
+    end
+  end
+
+  defp label_to_ascii(label) do
+    cond do
+      String.starts_with?(label, @ace_prefix) ->
+        with {:ok, _unicode} <- label_to_unicode(label), do: check_length(label)
+
+      ascii?(label) ->
+        with :ok <- validate_label(label), do: check_length(label)
+
+      true ->
+        with :ok <- validate_label(label) do
+          check_length(@ace_prefix <> punycode_encode(label))
+        end
+    end
+  end
+
+  defp label_to_unicode(@ace_prefix <> encoded = label) do
+    with {:ok, decoded} <- wrap_punycode(punycode_decode(encoded), label),
+         :ok <- check_nfc(decoded, label),
+         :ok <- check_round_trip(decoded, label),
+         :ok <- validate_label(decoded) do
+      {:ok, decoded}
+    end
+  end
+
+  defp label_to_unicode(label) do
+    with :ok <- validate_label(label), do: {:ok, label}
+  end
+
+  defp wrap_punycode({:ok, decoded}, _label), do: {:ok, decoded}
+  defp wrap_punycode(:error, label), do: {:error, {:invalid_punycode, label}}
+
+  defp check_nfc(decoded, label) do
+    if :unicode.characters_to_nfc_binary(decoded) == decoded,
+      do: :ok,
+      else: {:error, {:not_nfc, label}}
+  end
+
+  # An xn-- label must decode to something that is not plain ASCII and must
+  # re-encode to itself, otherwise it is a non-canonical (spoofable) encoding.
+  defp check_round_trip(decoded, label) do
+    if decoded != "" and not ascii?(decoded) and @ace_prefix <> punycode_encode(decoded) == label,
+      do: :ok,
+      else: {:error, {:invalid_punycode, label}}
+  end
+
+  defp check_length(ascii_label) do
+    if byte_size(ascii_label) > @max_label_length,
+      do: {:error, {:label_too_long, ascii_label}},
+      else: {:ok, ascii_label}
+  end
+
+  defp validate_label(""), do: {:error, {:empty_label, ""}}
+
+  defp validate_label(label) do
+    cond do
+      String.starts_with?(label, "-") or String.ends_with?(label, "-") ->
+        {:error, {:hyphen_start_end, label}}
+
+      String.slice(label, 2, 2) == "--" ->
+        {:error, {:hyphen_3_4, label}}
+
+      Regex.match?(@leading_mark, label) ->
+        {:error, {:leading_combining_mark, label}}
+
+      String.contains?(label, ["\u200C", "\u200D"]) ->
+        {:error, {:contextj, label}}
+
+      disallowed = Enum.find(String.codepoints(label), &(not Regex.match?(@valid_char, &1))) ->
+        {:error, {{:disallowed, disallowed}, label}}
+
+      Regex.match?(@rtl_char, label) and
+          (not Regex.match?(@rtl_start, label) or Regex.match?(@ltr_letter, label)) ->
+        {:error, {:bidi, label}}
+
+      true ->
+        :ok
+    end
+  end
+
+  defp map_while_ok(list, fun) do
+    list
+    |> Enum.reduce_while({:ok, []}, fn item, {:ok, acc} ->
+      case fun.(item) do
+        {:ok, value} -> {:cont, {:ok, [value | acc]}}
+        {:error, _} = error -> {:halt, error}
+      end
+    end)
+    |> case do
+      {:ok, acc} -> {:ok, Enum.reverse(acc)}
+      error -> error
+    end
+  end
+
+  # =============================================================================
+  # Punycode (RFC 3492)
+  # =============================================================================
+
+  @doc """
+  Encodes a Unicode string with Punycode (without the `xn--` prefix).
+
+  ## Examples
+
+      iex> DomainTwistex.Permutate.IDNA.punycode_encode("münchen")
+      "mnchen-3ya"
+  """
+  def punycode_encode(string) do
+    code_points = String.to_charlist(string)
+    basic = Enum.filter(code_points, &(&1 < 0x80))
+    b = length(basic)
+    acc = if b > 0, do: [?- | Enum.reverse(basic)], else: []
+
+    encode_loop(code_points, length(code_points), @initial_n, 0, @initial_bias, b, b, acc)
+  end
+
+  defp encode_loop(_code_points, total, _n, _delta, _bias, h, _b, acc) when h >= total do
+    acc |> Enum.reverse() |> List.to_string()
+  end
+
+  defp encode_loop(code_points, total, n, delta, bias, h, b, acc) do
+    m = code_points |> Enum.filter(&(&1 >= n)) |> Enum.min()
+    delta = delta + (m - n) * (h + 1)
+
+    {delta, bias, h, acc} =
+      Enum.reduce(code_points, {delta, bias, h, acc}, fn c, {delta, bias, h, acc} ->
+        cond do
+          c < m ->
+            {delta + 1, bias, h, acc}
+
+          c == m ->
+            acc = encode_integer(delta, bias, @base, acc)
+            {0, adapt(delta, h + 1, h == b), h + 1, acc}
+
+          true ->
+            {delta, bias, h, acc}
+        end
+      end)
+
+    encode_loop(code_points, total, m + 1, delta + 1, bias, h, b, acc)
+  end
+
+  defp encode_integer(q, bias, k, acc) do
+    t = threshold(k, bias)
+
+    if q < t do
+      [encode_digit(q) | acc]
+    else
+      digit = encode_digit(t + rem(q - t, @base - t))
+      encode_integer(div(q - t, @base - t), bias, k + @base, [digit | acc])
+    end
+  end
+
+  @doc """
+  Decodes a Punycode string (without the `xn--` prefix).
+
+  Returns `{:ok, string}` or `:error` for malformed input.
+
+  ## Examples
+
+      iex> DomainTwistex.Permutate.IDNA.punycode_decode("bcher-kva")
+      {:ok, "bücher"}
+  """
+  def punycode_decode(string) do
+    {basic, encoded} =
+      case :binary.matches(string, "-") do
+        [] ->
+          {"", string}
+
+        matches ->
+          {pos, _} = List.last(matches)
+          {binary_part(string, 0, pos), binary_part(string, pos + 1, byte_size(string) - pos - 1)}
+      end
+
+    if ascii?(string) do
+      case decode_loop(String.to_charlist(encoded), @initial_n, 0, @initial_bias, String.to_charlist(basic)) do
+        {:ok, output} -> {:ok, List.to_string(output)}
+        :error -> :error
+      end
+    else
+      :error
+    end
+  rescue
+    # Overlong or out-of-range deltas produce invalid code points
+    ArgumentError -> :error
+    UnicodeConversionError -> :error
+  end
+
+  defp decode_loop([], _n, _i, _bias, output), do: {:ok, output}
+
+  defp decode_loop(input, n, i, bias, output) do
+    case decode_integer(input, i, 1, @base, bias) do
+      {:ok, new_i, rest} ->
+        len = length(output) + 1
+        bias = adapt(new_i - i, len, i == 0)
+        n = n + div(new_i, len)
+        pos = rem(new_i, len)
+
+        if n > 0x10FFFF or n in 0xD800..0xDFFF do
+          :error
+        else
+          decode_loop(rest, n, pos + 1, bias, List.insert_at(output, pos, n))
+        end
+
+      :error ->
+        :error
+    end
+  end
+
+  defp decode_integer([], _i, _w, _k, _bias), do: :error
+
+  defp decode_integer([c | rest], i, w, k, bias) do
+    case decode_digit(c) do
+      nil ->
+        :error
+
+      digit ->
+        i = i + digit * w
+        t = threshold(k, bias)
+
+        if digit < t,
+          do: {:ok, i, rest},
+          else: decode_integer(rest, i, w * (@base - t), k + @base, bias)
+    end
+  end
+
+  defp threshold(k, bias) when k <= bias, do: @tmin
+  defp threshold(k, bias) when k >= bias + @tmax, do: @tmax
+  defp threshold(k, bias), do: k - bias
+
+  defp adapt(delta, num_points, first_time) do
+    delta = if first_time, do: div(delta, @damp), else: div(delta, 2)
+    delta = delta + div(delta, num_points)
+    adapt_loop(delta, 0)
+  end
+
+  defp adapt_loop(delta, k) when delta > div((@base - @tmin) * @tmax, 2) do
+    adapt_loop(div(delta, @base - @tmin), k + @base)
+  end
+
+  defp adapt_loop(delta, k) do
+    k + div((@base - @tmin + 1) * delta, delta + @skew)
+  end
+
+  defp encode_digit(d) when d < 26, do: ?a + d
+  defp encode_digit(d), do: ?0 + d - 26
+
+  defp decode_digit(c) when c in ?0..?9, do: c - ?0 + 26
+  defp decode_digit(c) when c in ?A..?Z, do: c - ?A
+  defp decode_digit(c) when c in ?a..?z, do: c - ?a
+  defp decode_digit(_c), do: nil
+end
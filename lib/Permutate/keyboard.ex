@@ -0,0 +1,245 @@
+defmodule DomainTwistex.Permutate.Keyboard do
+  @moduledoc """
+  Named keyboard layouts used by the Insertion and Replacement generators and
+  by the keyboard proximity fuzzy score.
+
+  Each layout is a grid of rows with a horizontal offset per row, as on a
+  physical (staggered) keyboard. Key coordinates come from the grid, and two
+  keys are neighbours when their centres are at most 1.3 key widths apart,
+  which covers the keys left, right and diagonally above and below.
+
+    * `:qwerty` - US/UK
+    * `:qwertz` - German, Austrian, Swiss
+    * `:azerty` - French, Belgian
+    * `:dvorak` - US Dvorak
+    * `:colemak` - Colemak
+    * `:mobile` - phone touch keyboard (QWERTY letter rows only, with the
+      wider bottom-row offset of on-screen keyboards, and no digit row)
+
+  Only characters valid in a hostname label (`a-z`, `0-9` and `-`) are
+  returned as neighbours; punctuation keys only hold their place in the grid.
+
+  The Russian, Greek and Hebrew layouts (`script_layouts/0`) sit on the same
+  physical keys as the staggered Latin layouts, so `transcode/3` can tell what
+  a user types with the wrong layout active (see the LayoutSwitch generator).
+
+  ## The `:layouts` option
+
+  Generators and `DomainTwistex.Utils.calculate_fuzzy_scores/4` read the
+  layouts to apply from the `:layouts` option, either as a list of names or
+  as a keyword list of weights between 0 and 1:
+
+      layouts: [:qwertz, :azerty]
+      layouts: [qwertz: 1.0, azerty: 1.0, qwerty: 0.5]
+
+  Layouts are applied in the given order, which is also the order in which
+  an edit is attributed to a layout. Weights scale the keyboard proximity
+  score of edits made on that layout. The default is
+  `[qwerty: 1.0, qwertz: 1.0, azerty: 1.0]`.
+  """
+
+  @digits "1234567890"
+  @staggered [0, 0.5, 0.75, 1.25]
+
+  @grids [
+    qwerty: {[@digits, "qwertyuiop[]", "asdfghjkl;'", "zxcvbnm,./"], @staggered},
+    qwertz: {[@digits, "qwertzuiopü+", "asdfghjklöä", "yxcvbnm,.-"], @staggered},
+    azerty: {[@digits, "azertyuiop^$", "qsdfghjklmù", "wxcvbn,;:!"], @staggered},
+    dvorak: {[@digits, "',.pyfgcrl/=", "aoeuidhtns-", ";qjkxbmwvz"], @staggered},
+    colemak: {[@digits, "qwfpgjluy;[]", "arstdhneio'", "zxcvbkm,./"], @staggered},
+    mobile: {["qwertyuiop", "asdfghjkl", "zxcvbnm"], [0, 0.5, 1.5]}
+  ]
+
+  # Non-Latin layouts on the same physical keys, for LayoutSwitch
+  @script_grids [
+    russian: {[@digits, "йцукенгшщзхъ", "фывапролджэ", "ячсмитьбю."], @staggered},
+    greek: {[@digits, ";ςερτυθιοπ[]", "ασδφγηξκλ΄'", "ζχψωβνμ,./"], @staggered},
+    hebrew: {[@digits, "/'קראטוןםפ[]", "שדגכעיחלךף,", "זסבהנמצתץ."], @staggered}
+  ]
+
+  @neighbour_radius 1.3
+  @default_layouts [qwerty: 1.0, qwertz: 1.0, azerty: 1.0]
+
+  # Layout name => %{coordinates: %{char => {row, col}}, keys: %{{row, col} => char},
+  # neighbours: %{char => charlist}, offsets: row offsets}
+  @layouts (for {name, {rows, offsets}} <- @grids ++ @script_grids, into: %{} do
+              coordinates =
+                for {{row, offset}, r} <- Enum.with_index(Enum.zip(rows, offsets)),
+                    {c, i} <- Enum.with_index(String.to_charlist(row)),
+                    into: %{} do
+                  {c, {r, offset + i}}
+                end
+
+              hostname_char? = &(&1 in ?a..?z or &1 in ?0..?9 or &1 == ?-)
+
+              neighbours =
+                for {c, {r, col}} <- coordinates, hostname_char?.(c), into: %{} do
+                  near =
+                    for {k, {kr, kcol}} <- coordinates,
+                        k != c,
+                        hostname_char?.(k),
+                        distance = :math.sqrt((r - kr) * (r - kr) + (col - kcol) * (col - kcol)),
+                        distance <= @neighbour_radius do
+                      {distance, k}
+                    end
+
+                  {c, near |> Enum.sort() |> Enum.map(&elem(&1, 1))}
+                end
+
+              keys = Map.new(coordinates, fn {c, position} -> {position, c} end)
+
+              {name, %{coordinates: coordinates, keys: keys, neighbours: neighbours, offsets: offsets}}
+            end)
+
+  @doc """
+  Returns the names of all known layouts.
+
+  ## Examples
+
+      iex> DomainTwistex.Permutate.Keyboard.names()
+      [:qwerty, :qwertz, :azerty, :dvorak, :colemak, :mobile]
+  """
+  def names, do: Keyword.keys(@grids)
+
+  @doc """
+  Returns the names of the non-Latin layouts (Russian ЙЦУКЕН, Greek and
+  Hebrew), which only serve to model typing with the wrong layout active.
+  They cannot be selected with `:layouts`.
+  """
+  def script_layouts, do: Keyword.keys(@script_grids)
+
+  @doc """
+  Returns the keys neighbouring `char` on `layout`, closest first.
+
+  ## Examples
+
+      iex> DomainTwistex.Permutate.Keyboard.neighbours(:qwertz, ?t)
+      ~c"rzg56f"
+  """
+  def neighbours(layout, char), do: @layouts |> Map.fetch!(layout) |> Map.fetch!(:neighbours) |> Map.get(char, [])
+
+  @doc """
+  Returns true when `key` neighbours `char` on `layout`.
+  """
+  def neighbour?(layout, char, key), do: key in neighbours(layout, char)
+
+  @doc """
+  Returns the distance between the centres of two keys on `layout`, in key
+  widths, or `nil` when either key is not on the layout.
+
+  ## Examples
+
+      iex> DomainTwistex.Permutate.Keyboard.distance(:qwerty, ?a, ?s)
+      1.0
+
+      iex> DomainTwistex.Permutate.Keyboard.distance(:mobile, ?a, ?1)
+      nil
+  """
+  def distance(layout, a, b) do
+    coordinates = @layouts |> Map.fetch!(layout) |> Map.fetch!(:coordinates)
+
+    case {Map.get(coordinates, a), Map.get(coordinates, b)} do
+      {{r1, c1}, {r2, c2}} -> :math.sqrt((r1 - r2) * (r1 - r2) + (c1 - c2) * (c1 - c2))
+      _ -> nil
+    end
+  end
+
+  @doc """
+  Returns true when every character of `text` is a key of `layout`, apart
+  from hyphens.
+  """
+  def typeable?(text, layout) do
+    coordinates = @layouts |> Map.fetch!(layout) |> Map.fetch!(:coordinates)
+    text |> String.to_charlist() |> Enum.all?(&(&1 == ?- or Map.has_key?(coordinates, &1)))
+  end
+
+  @doc """
+  Retypes `text` on another layout: every key pressed for `text` on `from`
+  is read as the character at the same physical position on `to`, which is
+  what a user gets with the wrong layout active. Characters that are not
+  keys of `from` are kept.
+
+  Returns `:error` when a key has no counterpart on `to`, or when the two
+  layouts do not share the same physical geometry (`:mobile`).
+
+  ## Examples
+
+      iex> DomainTwistex.Permutate.Keyboard.transcode("ghbdtn", :qwerty, :russian)
+      {:ok, "привет"}
+
+      iex> DomainTwistex.Permutate.Keyboard.transcode("привет", :russian, :qwerty)
+      {:ok, "ghbdtn"}
+  """
+  def transcode(text, from, to) do
+    %{coordinates: coordinates, offsets: offsets} = Map.fetch!(@layouts, from)
+    %{keys: keys, offsets: to_offsets} = Map.fetch!(@layouts, to)
+
+    if offsets == to_offsets do
+      text
+      |> String.to_charlist()
+      |> Enum.reduce_while({:ok, []}, fn c, {:ok, acc} ->
+        case Map.fetch(coordinates, c) do
+          {:ok, position} ->
+            case Map.fetch(keys, position) do
+              {:ok, key} -> {:cont, {:ok, [key | acc]}}
+              :error -> {:halt, :error}
+            end
+
+          :error ->
+            {:cont, {:ok, [c | acc]}}
+        end
+      end)
+      |> case do
+        {:ok, chars} -> {:ok, chars |> Enum.reverse() |> List.to_string()}
+        :error -> :error
+      end
+    else
+      :error
+    end
+  end
+
+  @doc """
+  Returns the layouts selected by the `:layouts` option as `{name, weight}`
+  tuples, in order.
+
+  ## Examples
+
+      iex> DomainTwistex.Permutate.Keyboard.layouts(layouts: [:qwertz, azerty: 0.5])
+      [qwertz: 1.0, azerty: 0.5]
+
+      iex> DomainTwistex.Permutate.Keyboard.layouts([]) |> Keyword.keys()
+      [:qwerty, :qwertz, :azerty]
+  """
+  def layouts(opts) do
+    opts
+    |> Keyword.get(:layouts, @default_layouts)
+    |> Enum.map(fn
+      {name, weight} -> {name, weight / 1}
+      name -> {name, 1.0}
+    end)
+  end
+
+  @doc """
+  Validates the `:layouts` option, for generators that read it.
+  """
+  def validate_opts(opts) do
+    case Keyword.get(opts, :layouts, @default_layouts) do
+      [_ | _] = layouts ->
+        case Enum.reject(layouts, &valid_layout?/1) do
+          [] ->
+            :ok
+
+          invalid ->
+            {:error,
+             ":layouts entries must be layout names or {name, weight} with 0 < weight <= 1, " <>
+               "got: #{inspect(invalid)}. Known layouts: #{Enum.map_join(names(), ", ", &inspect/1)}"}
+        end
+
+      other ->
+        {:error, ":layouts must be a non-empty list of layout names, got: #{inspect(other)}"}
+    end
+  end
+
+  defp valid_layout?({name, weight}) when is_number(weight), do: weight > 0 and weight <= 1 and valid_layout?(name)
+  defp valid_layout?(name), do: name in names()
+end
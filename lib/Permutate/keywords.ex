@@ -0,0 +1,121 @@
+defmodule DomainTwistex.Permutate.Keywords do
+  @moduledoc """
+  Keyword dictionaries for the Keyword generator, selected at runtime with
+  the `:keywords` option.
+
+  Every keyword has a category, the kind of lure it makes:
+
+    * `"credential"` - sign-in and account words (`login`, `verify`, `reset`)
+    * `"support"` - help desk words (`support`, `helpdesk`)
+    * `"payment"` - billing and banking words (`pay`, `invoice`)
+    * `"shipping"` - parcel words (`delivery`, `tracking`)
+    * `"promo"` - offers (`coupon`, `giveaway`)
+    * `"generic"` - everything else
+
+  ## The `:keywords` option
+
+      keywords: :banking                          # a named pack
+      keywords: "/etc/brand/keywords.txt"         # a file, read at runtime
+      keywords: ["portal", {"sso", "credential"}] # keywords, with or without a category
+      keywords: [:default, :saas, {:file, path}]  # packs, files and keywords mixed
+
+  Packs are `:default` (`priv/keywords.txt` or the `:keywords_path` file,
+  see `DomainTwistex.Permutate.Data`; used when the option is not given),
+  `:banking`, `:crypto`, `:saas` and `:retail`. Files hold one keyword per
+  line, optionally followed by its category; blank lines and lines starting
+  with `#` are skipped. Keywords without a category take the one of the
+  default list, or `"generic"`. Multi-word keywords may be written with
+  underscores (`customer_service`), see the `:keyword_joins` option of the
+  Keyword generator.
+
+  Files are read on every `load/1`; `DomainTwistex.Permutate.stream/2` and
+  `DomainTwistex.Permutate.classify/3` load the keywords once per call.
+  """
+
+  alias DomainTwistex.Permutate.Data
+
+  @pack_paths Path.wildcard(Path.join([:code.priv_dir(:domaintwistex), "keyword_packs", "*.txt"]))
+  for path <- @pack_paths, do: @external_resource(path)
+
+  # Pack name => file contents, parsed on use
+  @packs Map.new(@pack_paths, &{&1 |> Path.basename(".txt") |> String.to_atom(), File.read!(&1)})
+
+  @default_categories (for {category, keywords} <- Data.keyword_categories(), keyword <- keywords, into: %{} do
+                         {keyword, category}
+                       end)
+
+  @doc """
+  Returns the names of the keyword packs.
+
+  ## Examples
+
+      iex> DomainTwistex.Permutate.Keywords.packs()
+      [:default, :banking, :crypto, :retail, :saas]
+  """
+  def packs, do: [:default | @packs |> Map.keys() |> Enum.sort()]
+
+  @doc """
+  Returns the keywords selected by the `:keywords` option as
+  `{keyword, category}` tuples, in order and without duplicates.
+
+  ## Examples
+
+      iex> DomainTwistex.Permutate.Keywords.load(keywords: ["login", {"acme", "brand"}, "widgets"])
+      [{"login", "credential"}, {"acme", "brand"}, {"widgets", "generic"}]
+  """
+  def load(opts) do
+    opts
+    |> Keyword.get(:keywords, :default)
+    |> sources()
+    |> Enum.flat_map(&entries/1)
+    |> Enum.uniq_by(&elem(&1, 0))
+  end
+
+  @doc """
+  Validates the `:keywords` option, for generators that read it.
+  """
+  def validate_opts(opts) do
+    case opts |> Keyword.get(:keywords, :default) |> sources() |> Enum.reject(&valid_source?/1) do
+      [] ->
+        :ok
+
+      invalid ->
+        {:error,
+         ":keywords entries must be pack names, readable files or non-empty keywords, got: #{inspect(invalid)}. " <>
+           "Known packs: #{Enum.map_join(packs(), ", ", &inspect/1)}"}
+    end
+  end
+
+  defp sources(path) when is_binary(path), do: [{:file, path}]
+  defp sources(sources) when is_list(sources), do: sources
+  defp sources(source), do: [source]
+
+  defp entries(:default), do: Enum.map(Data.keywords(), &entry(&1, nil))
+  defp entries({:file, path}), do: path |> File.read!() |> parse()
+  defp entries({keyword, category}), do: [entry(keyword, category)]
+  defp entries(keyword) when is_binary(keyword), do: [entry(keyword, nil)]
+  defp entries(pack), do: @packs |> Map.fetch!(pack) |> parse()
+
+  defp parse(contents) do
+    for line <- String.split(contents, ~r/\R/),
+        line = String.trim(line),
+        line != "" and not String.starts_with?(line, "#") do
+      case String.split(line, ~r/\s+/, parts: 2) do
+        [keyword, category] -> entry(keyword, category)
+        [keyword] -> entry(keyword, nil)
+      end
+    end
+  end
+
+  defp entry(keyword, category) do
+    keyword = keyword |> String.trim() |> String.downcase()
+    {keyword, category || Map.get(@default_categories, keyword, "generic")}
+  end
+
+  defp valid_source?(:default), do: true
+  defp valid_source?({:file, path}) when is_binary(path), do: File.regular?(path)
+  defp valid_source?({keyword, category}) when is_binary(keyword) and is_binary(category), do: String.trim(keyword) != ""
+  defp valid_source?(keyword) when is_binary(keyword), do: String.trim(keyword) != ""
+  defp valid_source?(pack) when is_atom(pack), do: Map.has_key?(@packs, pack)
+  defp valid_source?(_source), do: false
+end
@@ -0,0 +1,122 @@
+defmodule DomainTwistex.Permutate.Likelihood do
+  @moduledoc """
+  Prior likelihood that a candidate is a dangerous look-alike, computed
+  before any resolution so that scans with a tight budget can check the
+  riskiest candidates first.
+
+  The score, between 0 and 1, is a weighted sum of:
+
+    * the kind's base rate among registered look-alikes
+      (`Data.kind_base_rates/0`), multiplied along depth-2 chains - 35%
+    * visual similarity: 1.0 for confusables (same UTS #39 skeleton),
+      otherwise the Jaro similarity of the names - 30%
+    * keyboard distance: edits made with a neighbouring key score by how
+      close the keys are and the layout's weight, other single-character
+      slips get 0.6 and other edits 0.3 - 15%
+    * TLD popularity: 1.0 for the input's own suffix, otherwise
+      `Data.tld_popularity/0` - 20%
+  """
+
+  alias DomainTwistex.Permutate
+  alias DomainTwistex.Permutate.Confusables
+  alias DomainTwistex.Permutate.Data
+  alias DomainTwistex.Permutate.Keyboard
+
+  @kind_base_rates Data.kind_base_rates()
+  @tld_popularity Data.tld_popularity()
+  @default_kind_rate 0.5
+  @default_tld_popularity 0.2
+
+  @weights [kind: 0.35, visual: 0.3, keyboard: 0.15, tld: 0.2]
+
+  @doc """
+  Scores a candidate from `DomainTwistex.Permutate.stream/2` against the
+  input domain.
+
+  ## Parameters
+    - candidate: Candidate map with `:fqdn`, `:unicode`, `:tld`, `:kind` and `:edit`
+    - domain: The input domain, or its parts from `DomainTwistex.Permutate.parse_domain/1`
+    - opts: `:layouts`, to weight keyboard edits
+
+  ## Returns
+    A float between 0 and 1, higher meaning more likely to be abused.
+  """
+  def score(candidate, domain, opts \\ [])
+
+  def score(candidate, domain, opts) when is_binary(domain), do: score(candidate, Permutate.parse_domain(domain), opts)
+
+  def score(candidate, {subdomain, label, suffix}, opts) do
+    input = if subdomain == "", do: "#{label}.#{suffix}", else: "#{subdomain}.#{label}.#{suffix}"
+
+    components = [
+      kind: kind_rate(candidate.kind),
+      visual: visual(Map.get(candidate, :unicode, candidate.fqdn), candidate.tld, input, suffix),
+      keyboard: keyboard(Map.get(candidate, :edit), opts),
+      tld: tld_popularity(candidate.tld, suffix)
+    ]
+
+    @weights
+    |> Enum.map(fn {component, weight} -> weight * Keyword.fetch!(components, component) end)
+    |> Enum.sum()
+    |> Float.round(4)
+  end
+
+  @doc """
+  Scores candidates, most likely first, with the score under `:likelihood`.
+
+  Candidates are materialized to be sorted. Ties keep generation order.
+
+  ## Parameters
+    - candidates: Enumerable of candidate maps
+    - domain: The input domain
+    - opts: `:top_n` keeps only that many candidates; `:layouts` as in `score/3`
+  """
+  def rank(candidates, domain, opts \\ []) do
+    parts = Permutate.parse_domain(domain)
+
+    ranked =
+      candidates
+      |> Enum.map(&Map.put(&1, :likelihood, score(&1, parts, opts)))
+      |> Enum.sort_by(& &1.likelihood, :desc)
+
+    case Keyword.get(opts, :top_n) do
+      nil -> ranked
+      top_n -> Enum.take(ranked, top_n)
+    end
+  end
+
+  defp kind_rate(kinds) when is_list(kinds), do: kinds |> Enum.map(&kind_rate/1) |> Enum.product()
+  defp kind_rate(kind), do: Map.get(@kind_base_rates, kind, @default_kind_rate)
+
+  defp tld_popularity(suffix, suffix), do: 1.0
+  defp tld_popularity(tld, _suffix), do: Map.get(@tld_popularity, tld, @default_tld_popularity)
+
+  defp visual(unicode, tld, input, suffix) do
+    if Confusables.confusable?(unicode, input) do
+      1.0
+    else
+      String.jaro_distance(String.replace_suffix(unicode, "." <> tld, ""), String.replace_suffix(input, "." <> suffix, ""))
+    end
+  end
+
+  # Depth-2 candidates carry one edit per kind: the closest slip counts
+  defp keyboard(edits, opts) when is_list(edits), do: edits |> Enum.map(&keyboard(&1, opts)) |> Enum.max(fn -> 0.3 end)
+
+  defp keyboard(%{layout: layout} = edit, opts) do
+    weight = opts |> Keyboard.layouts() |> Keyword.get(layout, 1.0)
+
+    closeness =
+      case edit do
+        %{original: <<a::utf8>>, replacement: <<b::utf8>>} -> 1.0 - ((Keyboard.distance(layout, a, b) || 1.3) - 1.0)
+        _ -> 0.9
+      end
+
+    weight * closeness
+  end
+
+  defp keyboard(%{position: _, original: original, replacement: replacement}, _opts)
+       when byte_size(original) <= 2 and byte_size(replacement) <= 2,
+       do: 0.6
+
+  defp keyboard(_edit, _opts), do: 0.3
+end
@@ -0,0 +1,448 @@
+defmodule DomainTwistex.Permutate.Phonetic do
+  @moduledoc """
+  Double Metaphone phonetic encoding (Lawrence Philips, 2000).
+
+  Encodes a word as a primary and an alternate key that approximate its
+  English pronunciation, so that soundalikes such as `phone` and `fone` or
+  `quick` and `kwik` get the same key. Used by the Phonetic generator.
+
+  Keys are not truncated to four characters as in the original algorithm:
+  domain labels are often long brand names, and truncated keys would match
+  unrelated labels. Characters other than ASCII letters are ignored.
+  """
+
+  @vowels ["A", "E", "I", "O", "U", "Y"]
+
+  @doc """
+  Returns the `{primary, alternate}` Double Metaphone keys of a word.
+
+  ## Examples
+
+      iex> DomainTwistex.Permutate.Phonetic.double_metaphone("phone")
+      {"FN", "FN"}
+
+      iex> DomainTwistex.Permutate.Phonetic.double_metaphone("quick") ==
+      ...>   DomainTwistex.Permutate.Phonetic.double_metaphone("kwik")
+      true
+
+      iex> DomainTwistex.Permutate.Phonetic.double_metaphone("schmidt")
+      {"XMT", "SMT"}
+  """
+  def double_metaphone(word) do
+    word = word |> String.upcase() |> String.replace(~r/[^A-Z]/, "")
+
+    ctx = %{
+      last: byte_size(word) - 1,
+      slavo_germanic: String.contains?(word, ["W", "K", "CZ", "WITZ"])
+    }
+
+    start =
+      cond do
+        word == "" -> 0
+        at?(word, 0, 2, ["GN", "KN", "PN", "WR", "PS"]) -> 1
+        true -> 0
+      end
+
+    # Initial "x" sounds like "s" (xavier)
+    if at(word, 0) == "X" do
+      encode(word, 1, ctx, ["S"], ["S"])
+    else
+      encode(word, start, ctx, [], [])
+    end
+  end
+
+  @doc """
+  Returns the distinct non-empty keys of a word (one or two).
+
+  ## Examples
+
+      iex> DomainTwistex.Permutate.Phonetic.keys("smith")
+      ["SM0", "XMT"]
+  """
+  def keys(word) do
+    {primary, alternate} = double_metaphone(word)
+    Enum.uniq(Enum.reject([primary, alternate], &(&1 == "")))
+  end
+
+  @doc """
+  Returns true when two words share a Double Metaphone key.
+
+  ## Examples
+
+      iex> DomainTwistex.Permutate.Phonetic.soundalike?("right", "rite")
+      true
+
+      iex> DomainTwistex.Permutate.Phonetic.soundalike?("right", "light")
+      false
+  """
+  def soundalike?(word, other) do
+    keys = keys(word)
+    keys != [] and Enum.any?(keys(other), &(&1 in keys))
+  end
+
+  defp encode(_word, i, %{last: last}, primary, alternate) when i > last do
+    {primary |> Enum.reverse() |> Enum.join(), alternate |> Enum.reverse() |> Enum.join()}
+  end
+
+  defp encode(word, i, ctx, primary, alternate) do
+    {p, a, step} = code(word, i, ctx)
+    encode(word, i + step, ctx, [p | primary], [a | alternate])
+  end
+
+  # Returns {primary, alternate, characters consumed} for the character at i
+  defp code(word, i, ctx) do
+    case at(word, i) do
+      c when c in @vowels ->
+        if i == 0, do: {"A", "A", 1}, else: {"", "", 1}
+
+      "B" ->
+        {"P", "P", skip_double(word, i, ["B"])}
+
+      "C" ->
+        code_c(word, i, ctx)
+
+      "D" ->
+        cond do
+          at?(word, i, 2, ["DG"]) ->
+            if at?(word, i + 2, 1, ["I", "E", "Y"]), do: {"J", "J", 3}, else: {"TK", "TK", 2}
+
+          at?(word, i, 2, ["DT", "DD"]) ->
+            {"T", "T", 2}
+
+          true ->
+            {"T", "T", 1}
+        end
+
+      "F" ->
+        {"F", "F", skip_double(word, i, ["F"])}
+
+      "G" ->
+        code_g(word, i, ctx)
+
+      "H" ->
+        if (i == 0 or vowel?(word, i - 1)) and vowel?(word, i + 1), do: {"H", "H", 2}, else: {"", "", 1}
+
+      "J" ->
+        code_j(word, i, ctx)
+
+      "K" ->
+        {"K", "K", skip_double(word, i, ["K"])}
+
+      "L" ->
+        cond do
+          at(word, i + 1) != "L" ->
+            {"L", "L", 1}
+
+          (i == ctx.last - 2 and at?(word, i - 1, 4, ["ILLO", "ILLA", "ALLE"])) or
+              ((at?(word, ctx.last - 1, 2, ["AS", "OS"]) or at?(word, ctx.last, 1, ["A", "O"])) and
+                 at?(word, i - 1, 4, ["ALLE"])) ->
+            {"L", "", 2}
+
+          true ->
+            {"L", "L", 2}
+        end
+
+      "M" ->
+        step =
+          if (at?(word, i - 1, 3, ["UMB"]) and (i + 1 == ctx.last or at?(word, i + 2, 2, ["ER"]))) or
+               at(word, i + 1) == "M",
+             do: 2,
+             else: 1
+
+        {"M", "M", step}
+
+      "N" ->
+        {"N", "N", skip_double(word, i, ["N"])}
+
+      "P" ->
+        if at(word, i + 1) == "H", do: {"F", "F", 2}, else: {"P", "P", skip_double(word, i, ["P", "B"])}
+
+      "Q" ->
+        {"K", "K", skip_double(word, i, ["Q"])}
+
+      "R" ->
+        step = skip_double(word, i, ["R"])
+
+        if i == ctx.last and not ctx.slavo_germanic and at?(word, i - 2, 2, ["IE"]) and
+             not at?(word, i - 4, 2, ["ME", "MA"]) do
+          {"", "R", step}
+        else
+          {"R", "R", step}
+        end
+
+      "S" ->
+        code_s(word, i, ctx)
+
+      "T" ->
+        cond do
+          at?(word, i, 4, ["TION"]) or at?(word, i, 3, ["TIA", "TCH"]) ->
+            {"X", "X", 3}
+
+          at?(word, i, 2, ["TH"]) or at?(word, i, 3, ["TTH"]) ->
+            if at?(word, i + 2, 2, ["OM", "AM"]) or at?(word, 0, 3, ["SCH"]),
+              do: {"T", "T", 2},
+              else: {"0", "T", 2}
+
+          true ->
+            {"T", "T", skip_double(word, i, ["T", "D"])}
+        end
+
+      "V" ->
+        {"F", "F", skip_double(word, i, ["V"])}
+
+      "W" ->
+        code_w(word, i, ctx)
+
+      "X" ->
+        step = skip_double(word, i, ["C", "X"])
+
+        if i == ctx.last and (at?(word, i - 3, 3, ["IAU", "EAU"]) or at?(word, i - 2, 2, ["AU", "OU"])) do
+          {"", "", step}
+        else
+          {"KS", "KS", step}
+        end
+
+      "Z" ->
+        cond do
+          at(word, i + 1) == "H" ->
+            {"J", "J", 2}
+
+          at?(word, i + 1, 2, ["ZO", "ZI", "ZA"]) or
+              (ctx.slavo_germanic and i > 0 and at(word, i - 1) != "T") ->
+            {"S", "TS", skip_double(word, i, ["Z"])}
+
+          true ->
+            {"S", "S", skip_double(word, i, ["Z"])}
+        end
+
+      _ ->
+        {"", "", 1}
+    end
+  end
+
+  defp code_c(word, i, _ctx) do
+    cond do
+      # Germanic "ach" (bacher, macher)
+      i > 1 and not vowel?(word, i - 2) and at?(word, i - 1, 3, ["ACH"]) and at(word, i + 2) != "I" and
+          (at(word, i + 2) != "E" or at?(word, i - 2, 6, ["BACHER", "MACHER"])) ->
+        {"K", "K", 2}
+
+      i == 0 and at?(word, i, 6, ["CAESAR"]) ->
+        {"S", "S", 2}
+
+      at?(word, i, 4, ["CHIA"]) ->
+        {"K", "K", 2}
+
+      at?(word, i, 2, ["CH"]) ->
+        code_ch(word, i)
+
+      at?(word, i, 2, ["CZ"]) and not at?(word, i - 2, 4, ["WICZ"]) ->
+        {"S", "X", 2}
+
+      at?(word, i + 1, 3, ["CIA"]) ->
+        {"X", "X", 3}
+
+      at?(word, i, 2, ["CC"]) and not (i == 1 and at(word, 0) == "M") ->
+        if at?(word, i + 2, 1, ["I", "E", "H"]) and not at?(word, i + 2, 2, ["HU"]) do
+          if (i == 1 and at(word, 0) == "A") or at?(word, i - 1, 5, ["UCCEE", "UCCES"]),
+            do: {"KS", "KS", 3},
+            else: {"X", "X", 3}
+        else
+          {"K", "K", 2}
+        end
+
+      at?(word, i, 2, ["CK", "CG", "CQ"]) ->
+        {"K", "K", 2}
+
+      at?(word, i, 2, ["CI", "CE", "CY"]) ->
+        if at?(word, i, 3, ["CIO", "CIE", "CIA"]), do: {"S", "X", 2}, else: {"S", "S", 2}
+
+      at?(word, i + 1, 1, ["C", "K", "Q"]) and not at?(word, i + 1, 2, ["CE", "CI"]) ->
+        {"K", "K", 2}
+
+      true ->
+        {"K", "K", 1}
+    end
+  end
+
+  defp code_ch(word, i) do
+    cond do
+      i > 0 and at?(word, i, 4, ["CHAE"]) ->
+        {"K", "X", 2}
+
+      # Greek roots (chemistry, chorus)
+      i == 0 and (at?(word, i + 1, 5, ["HARAC", "HARIS"]) or at?(word, i + 1, 3, ["HOR", "HYM", "HIA", "HEM"])) and
+          not at?(word, 0, 5, ["CHORE"]) ->
+        {"K", "K", 2}
+
+      at?(word, 0, 3, ["SCH"]) or at?(word, i - 2, 6, ["ORCHES", "ARCHIT", "ORCHID"]) or
+        at?(word, i + 2, 1, ["T", "S"]) or
+          ((i == 0 or at?(word, i - 1, 1, ["A", "O", "U", "E"])) and
+             at?(word, i + 2, 1, ["L", "R", "N", "M", "B", "H", "F", "V", "W"])) ->
+        {"K", "K", 2}
+
+      i > 0 ->
+        if at?(word, 0, 2, ["MC"]), do: {"K", "K", 2}, else: {"X", "K", 2}
+
+      true ->
+        {"X", "X", 2}
+    end
+  end
+
+  defp code_g(word, i, ctx) do
+    cond do
+      at(word, i + 1) == "H" ->
+        cond do
+          i > 0 and not vowel?(word, i - 1) ->
+            {"K", "K", 2}
+
+          i == 0 ->
+            if at(word, i + 2) == "I", do: {"J", "J", 2}, else: {"K", "K", 2}
+
+          # Silent in "bough", "hugh", "broughton"
+          (i > 1 and at?(word, i - 2, 1, ["B", "H", "D"])) or (i > 2 and at?(word, i - 3, 1, ["B", "H", "D"])) or
+              (i > 3 and at?(word, i - 4, 1, ["B", "H"])) ->
+            {"", "", 2}
+
+          # "laugh", "cough", "rough"
+          i > 2 and at(word, i - 1) == "U" and at?(word, i - 3, 1, ["C", "G", "L", "R", "T"]) ->
+            {"F", "F", 2}
+
+          i > 0 and at(word, i - 1) != "I" ->
+            {"K", "K", 2}
+
+          true ->
+            {"", "", 2}
+        end
+
+      at(word, i + 1) == "N" ->
+        cond do
+          i == 1 and vowel?(word, 0) and not ctx.slavo_germanic -> {"KN", "N", 2}
+          not at?(word, i + 2, 2, ["EY"]) and not ctx.slavo_germanic -> {"N", "KN", 2}
+          true -> {"KN", "KN", 2}
+        end
+
+      at?(word, i + 1, 2, ["LI"]) and not ctx.slavo_germanic ->
+        {"KL", "L", 2}
+
+      i == 0 and
+          (at(word, i + 1) == "Y" or
+             at?(word, i + 1, 2, ["ES", "EP", "EB", "EL", "EY", "IB", "IL", "IN", "IE", "EI", "ER"])) ->
+        {"K", "J", 2}
+
+      (at?(word, i + 1, 2, ["ER"]) or at(word, i + 1) == "Y") and
+        not at?(word, 0, 6, ["DANGER", "RANGER", "MANGER"]) and not at?(word, i - 1, 1, ["E", "I"]) and
+          not at?(word, i - 1, 3, ["RGY", "OGY"]) ->
+        {"K", "J", 2}
+
+      at?(word, i + 1, 1, ["E", "I", "Y"]) or at?(word, i - 1, 4, ["AGGI", "OGGI"]) ->
+        cond do
+          at?(word, 0, 3, ["SCH"]) or at?(word, i + 1, 2, ["ET"]) -> {"K", "K", 2}
+          at?(word, i + 1, 3, ["IER"]) and i + 3 == ctx.last -> {"J", "J", 2}
+          true -> {"J", "K", 2}
+        end
+
+      true ->
+        {"K", "K", skip_double(word, i, ["G"])}
+    end
+  end
+
+  defp code_j(word, i, ctx) do
+    step = skip_double(word, i, ["J"])
+
+    cond do
+      at?(word, i, 4, ["JOSE"]) ->
+        if i == 0 and i + 3 == ctx.last, do: {"H", "H", 1}, else: {"J", "H", 1}
+
+      i == 0 ->
+        {"J", "A", step}
+
+      vowel?(word, i - 1) and not ctx.slavo_germanic and at?(word, i + 1, 1, ["A", "O"]) ->
+        {"J", "H", step}
+
+      i == ctx.last ->
+        {"J", "", step}
+
+      not at?(word, i + 1, 1, ["L", "T", "K", "S", "N", "M", "B", "Z"]) and not at?(word, i - 1, 1, ["S", "K", "L"]) ->
+        {"J", "J", step}
+
+      true ->
+        {"", "", step}
+    end
+  end
+
+  defp code_s(word, i, ctx) do
+    cond do
+      # Silent in "island", "carlisle"
+      at?(word, i - 1, 3, ["ISL", "YSL"]) ->
+        {"", "", 1}
+
+      i == 0 and at?(word, i, 5, ["SUGAR"]) ->
+        {"X", "S", 1}
+
+      at?(word, i, 2, ["SH"]) ->
+        if at?(word, i + 1, 4, ["HEIM", "HOEK", "HOLM", "HOLZ"]), do: {"S", "S", 2}, else: {"X", "X", 2}
+
+      at?(word, i, 3, ["SIO", "SIA"]) ->
+        if ctx.slavo_germanic, do: {"S", "S", 3}, else: {"S", "X", 3}
+
+      (i == 0 and at?(word, i + 1, 1, ["M", "N", "L", "W"])) or at(word, i + 1) == "Z" ->
+        {"S", "X", skip_double(word, i, ["Z"])}
+
+      at?(word, i, 2, ["SC"]) ->
+        cond do
+          at(word, i + 2) == "H" and at?(word, i + 3, 2, ["ER", "EN"]) -> {"X", "SK", 3}
+          at(word, i + 2) == "H" and at?(word, i + 3, 2, ["OO", "UY", "ED", "EM"]) -> {"SK", "SK", 3}
+          at(word, i + 2) == "H" and i == 0 and not vowel?(word, 3) and at(word, 3) != "W" -> {"X", "S", 3}
+          at(word, i + 2) == "H" -> {"X", "X", 3}
+          at?(word, i + 2, 1, ["I", "E", "Y"]) -> {"S", "S", 3}
+          true -> {"SK", "SK", 3}
+        end
+
+      i == ctx.last and at?(word, i - 2, 2, ["AI", "OI"]) ->
+        {"", "S", 1}
+
+      true ->
+        {"S", "S", skip_double(word, i, ["S", "Z"])}
+    end
+  end
+
+  defp code_w(word, i, ctx) do
+    {p, a} =
+      cond do
+        i == 0 and vowel?(word, i + 1) -> {"A", "F"}
+        i == 0 and at(word, i + 1) == "H" -> {"A", "A"}
+        true -> {"", ""}
+      end
+
+    cond do
+      at?(word, i, 2, ["WR"]) ->
+        {"R", "R", 2}
+
+      (i == ctx.last and vowel?(word, i - 1)) or
+          at?(word, i - 1, 5, ["EWSKI", "EWSKY", "OWSKI", "OWSKY"]) or at?(word, 0, 3, ["SCH"]) ->
+        {p, a <> "F", 1}
+
+      at?(word, i, 4, ["WICZ", "WITZ"]) ->
+        {p <> "TS", a <> "FX", 4}
+
+      true ->
+        {p, a, 1}
+    end
+  end
+
+  # Consumes a doubled letter ("bb") as one
+  defp skip_double(word, i, followers), do: if(at(word, i + 1) in followers, do: 2, else: 1)
+
+  defp at(_word, i) when i < 0, do: ""
+  defp at(word, i) when i >= byte_size(word), do: ""
+  defp at(word, i), do: binary_part(word, i, 1)
+
+  defp at?(_word, start, _length, _options) when start < 0, do: false
+
+  defp at?(word, start, length, options) do
+    start + length <= byte_size(word) and binary_part(word, start, length) in options
+  end
+
+  defp vowel?(word, i), do: at(word, i) in @vowels
+end
@@ -0,0 +1,125 @@
+defmodule DomainTwistex.Permutate.PublicSuffix do
+  @moduledoc """
+  Public-suffix-aware domain splitting backed by the Mozilla Public Suffix List.
+
+  Splits an input name into its subdomain, registrable label and public suffix,
+  so that `login.example.co.uk` becomes `{"login", "example", "co.uk"}` rather
+  than being split on the first dot.
+
+  The list is bundled in `priv/public_suffix_list.dat` and compiled into the
+  module at build time. To refresh it, run:
+
+      mix update_public_suffix_list
+
+  """
+
+  @external_resource psl_path = Path.join(:code.priv_dir(:domaintwistex), "public_suffix_list.dat")
+
+  @rules psl_path
+         |> File.read!()
+         |> String.split("\n")
+         |> Enum.map(&String.trim/1)
+         |> Enum.reject(&(&1 == "" or String.starts_with?(&1, "//")))
+         |> Enum.map(&(&1 |> String.split() |> hd() |> String.downcase()))
+         |> MapSet.new()
+
+  @doc """
+  Splits a domain into `{subdomain, label, suffix}`.
+
+  The subdomain is `""` when the input is already a registrable domain.
+  Input is lowercased and a trailing root dot is ignored.
+
+  Returns `:error` when the input has no registrable label, e.g. when it is a
+  public suffix itself (`co.uk`) or a single label (`localhost`).
+
+  ## Examples
+
+      iex> DomainTwistex.Permutate.PublicSuffix.split("login.example.co.uk")
+      {:ok, {"login", "example", "co.uk"}}
+
+      iex> DomainTwistex.Permutate.PublicSuffix.split("example.com")
+      {:ok, {"", "example", "com"}}
+
+      iex> DomainTwistex.Permutate.PublicSuffix.split("co.uk")
+      :error
+  """
+  def split(fqdn) do
+    labels =
+      fqdn
+      |> String.downcase()
+      |> String.trim_trailing(".")
+      |> String.split(".")
+
+    suffix_length = suffix_length(labels)
+
+    case Enum.split(labels, length(labels) - suffix_length) do
+      {[], _suffix} ->
+        :error
+
+      {prefix, suffix} ->
+        {subdomain, [label]} = Enum.split(prefix, -1)
+        {:ok, {Enum.join(subdomain, "."), label, Enum.join(suffix, ".")}}
+    end
+  end
+
+  @doc """
+  Returns the public suffix of a domain, e.g. `"co.uk"` for `shop.example.co.uk`.
+
+  Names without a matching rule fall back to their last label, as the
+  implicit `*` rule of the list requires.
+  """
+  def public_suffix(fqdn) do
+    labels =
+      fqdn
+      |> String.downcase()
+      |> String.trim_trailing(".")
+      |> String.split(".")
+
+    labels
+    |> Enum.take(-suffix_length(labels))
+    |> Enum.join(".")
+  end
+
+  @doc """
+  Returns the registrable domain (label plus public suffix), or `nil` when
+  the input has no registrable label.
+
+  ## Examples
+
+      iex> DomainTwistex.Permutate.PublicSuffix.registrable_domain("shop.brand.com")
+      "brand.com"
+  """
+  def registrable_domain(fqdn) do
+    case split(fqdn) do
+      {:ok, {_subdomain, label, suffix}} -> "#{label}.#{suffix}"
+      :error -> nil
+    end
+  end
+
+  @doc """
+  Returns true when the name is listed as a public suffix.
+  """
+  def public_suffix?(name) do
+    labels = name |> String.downcase() |> String.trim_trailing(".") |> String.split(".")
+    suffix_length(labels) == length(labels)
+  end
+
+  # Number of trailing labels that form the public suffix. Candidates are
+  # tried longest first, so the first hit is the longest matching rule and
+  # exception rules (always one label longer than their wildcard) win.
+  defp suffix_length(labels) do
+    count = length(labels)
+
+    Enum.find_value(0..(count - 1)//1, 1, fn i ->
+      candidate = labels |> Enum.drop(i) |> Enum.join(".")
+      parent = labels |> Enum.drop(i + 1) |> Enum.join(".")
+
+      cond do
+        MapSet.member?(@rules, "!" <> candidate) -> count - i - 1
+        MapSet.member?(@rules, candidate) -> count - i
+        parent != "" and MapSet.member?(@rules, "*." <> parent) -> count - i
+        true -> nil
+      end
+    end)
+  end
+end
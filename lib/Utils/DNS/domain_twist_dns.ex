@@ -2,19 +2,49 @@ defmodule DomainTwistex.DNS do
   @moduledoc """
   Provides pure DNS query operations for domain names.
   Handles various DNS record types including A, CNAME, MX, TXT, and NS records.
-  All functions use Erlang's :inet_res module for DNS resolution.
+
+  Queries go through a `DomainTwistex.DNS.Resolver`, chosen with the
+  `:resolver` option every function takes (see
+  `DomainTwistex.DNS.Resolver.from_opts/1`). The default is Erlang's
+  :inet_res with the system resolver configuration.
   """
 
+  alias DomainTwistex.DNS.Resolver
+
+  @doc """
+  Looks up the records of one type for a name with the resolver from `opts`.
+
+  ## Parameters
+    * name - The name to look up
+    * type - `:a`, `:aaaa`, `:cname`, `:mx`, `:ns` or `:txt`
+    * opts - `:resolver`, a module or `{module, opts}`
+
+  ## Returns
+    * `{:ok, [record]}` - The records (see `t:DomainTwistex.DNS.Resolver.record/0`)
+    * `{:error, reason}` - Such as `:nxdomain`, `:servfail` or `:timeout`
+
+  ## Example
+      ```
+      iex> DomainTwistex.DNS.lookup("example.com", :a, resolver: {DomainTwistex.DNS.Resolver.DoH, []})
+      {:ok, ["93.184.216.34"]}
+      ```
+  """
+  def lookup(name, type, opts \\ []) do
+    {resolver, resolver_opts} = Resolver.from_opts(opts)
+    resolver.lookup(name, type, resolver_opts)
+  end
+
   @doc """
   Resolves IP addresses for a given domain, handling both A and CNAME records.
 
   ## Parameters
     * domain - String representing the domain to resolve
+    * opts - `:resolver` (see `lookup/3`)
 
   ## Returns
     * `{:ok, %{ips: [string], cname: string | nil}}` - Resolved DNS information
     * `{:error, :no_records}` - When no records are found
-    * `{:error, :invalid_response}` - When DNS lookup returns invalid response
+    * `{:error, reason}` - When the resolver fails, e.g. `:servfail` or `:timeout`
 
   ## Example
       ```
@@ -22,19 +52,19 @@ defmodule DomainTwistex.DNS do
       {:ok, %{ips: ["93.184.216.34"], cname: nil}}
       ```
   """
-  def resolve_ips(domain) do
-    cname_result = :inet_res.lookup(String.to_charlist(domain), :in, :cname)
-    a_records = lookup_a_records(domain)
+  def resolve_ips(domain, opts \\ []) do
+    cname_result = lookup(domain, :cname, opts)
+    a_records = lookup_a_records(domain, opts)
 
     case {cname_result, a_records} do
+      # One or more CNAMEs, has A records - take first CNAME
+      {{:ok, [cname | _]}, {:ok, ips}} ->
+        {:ok, %{ips: ips, cname: cname}}
+
       # No CNAME, has A records
-      {[], {:ok, ips}} ->
+      {_, {:ok, ips}} ->
         {:ok, %{ips: ips, cname: nil}}
 
-      # One or more CNAMEs, has A records - take first CNAME
-      {[cname | _], {:ok, ips}} ->
-        {:ok, %{ips: ips, cname: to_string(cname)}}
-
       # A record lookup failed
       {_, {:error, reason}} ->
         {:error, reason}
@@ -46,6 +76,7 @@ defmodule DomainTwistex.DNS do
 
   ## Parameters
     * domain - String representing the domain to query
+    * opts - `:resolver` (see `lookup/3`)
 
   ## Returns
     * `{:ok, [string]}` - List of nameserver hostnames
@@ -57,21 +88,20 @@ defmodule DomainTwistex.DNS do
       {:ok, ["ns1.example.com", "ns2.example.com"]}
       ```
   """
-  def get_nameservers(domain) do
+  def get_nameservers(domain, opts \\ []) do
     try do
-      case :inet_res.lookup(String.to_charlist(domain), :in, :ns) do
-        [] ->
+      case lookup(domain, :ns, opts) do
+        {:ok, []} ->
           {:error, "No nameservers found"}
 
-        nameservers when is_list(nameservers) ->
-          formatted_ns =
-            nameservers
-            |> Enum.map(&(to_string(&1) |> String.trim_trailing(".")))
+        {:ok, nameservers} ->
+          {:ok, nameservers}
 
-          {:ok, formatted_ns}
+        {:error, :nxdomain} ->
+          {:error, "No nameservers found"}
 
         {:error, reason} ->
-          {:error, "DNS lookup failed: #{reason}"}
+          {:error, "DNS lookup failed: #{inspect(reason)}"}
       end
     rescue
       e -> {:error, "Nameserver lookup error: #{Exception.message(e)}"}
@@ -83,6 +113,7 @@ defmodule DomainTwistex.DNS do
 
   ## Parameters
     * domain - String representing the domain to query
+    * opts - `:resolver` (see `lookup/3`)
 
   ## Returns
     * `{:ok, [map]}` - List of maps containing :priority and :server keys
@@ -94,25 +125,17 @@ defmodule DomainTwistex.DNS do
       {:ok, [%{priority: 10, server: "mail.example.com"}]}
       ```
   """
-  def get_mx_records(domain) do
+  def get_mx_records(domain, opts \\ []) do
     try do
-      case :inet_res.lookup(String.to_charlist(domain), :in, :mx) do
-        [] ->
-          {:ok, []}
-
-        mx_records when is_list(mx_records) ->
-          records =
-            Enum.map(mx_records, fn {priority, server} ->
-              %{
-                priority: priority,
-                server: server |> List.to_string()
-              }
-            end)
-
+      case lookup(domain, :mx, opts) do
+        {:ok, records} ->
           {:ok, records}
 
-        _ ->
-          {:error, :invalid_response}
+        {:error, :nxdomain} ->
+          {:ok, []}
+
+        {:error, _reason} ->
+          {:error, :lookup_failed}
       end
     rescue
       _ -> {:error, :lookup_failed}
@@ -124,6 +147,7 @@ defmodule DomainTwistex.DNS do
 
   ## Parameters
     * domain - String representing the domain to query
+    * opts - `:resolver` (see `lookup/3`)
 
   ## Returns
     * `{:ok, [string]}` - List of TXT record strings
@@ -135,14 +159,9 @@ defmodule DomainTwistex.DNS do
       {:ok, ["v=spf1 -all"]}
       ```
   """
-  def get_txt_records(domain) do
-    case :inet_res.resolve(String.to_charlist(domain), :in, :txt) do
-      {:ok, dns_response} ->
-        records =
-          dns_response
-          |> elem(3)
-          |> Enum.map(&(elem(&1, 6) |> List.to_string()))
-
+  def get_txt_records(domain, opts \\ []) do
+    case lookup(domain, :txt, opts) do
+      {:ok, records} ->
         {:ok, records}
 
       {:error, reason} ->
@@ -157,60 +176,62 @@ defmodule DomainTwistex.DNS do
 
   ## Parameters
     * domain - String representing the domain to check
+    * opts - `:resolver` (see `lookup/3`)
 
   ## Returns
     * `{:ok, boolean}` - true if wildcard DNS is detected
   """
-  def has_wildcard(domain) do
+  def has_wildcard(domain, opts \\ []) do
     # Generate a random subdomain that shouldn't exist
     random_sub = :crypto.strong_rand_bytes(12) |> Base.encode16(case: :lower)
     test_domain = "#{random_sub}.#{domain}"
 
-    case :inet_res.lookup(String.to_charlist(test_domain), :in, :a) do
-      [] -> {:ok, false}
-      ips when is_list(ips) and length(ips) > 0 -> {:ok, true}
+    case lookup(test_domain, :a, opts) do
+      {:ok, [_ | _]} -> {:ok, true}
       _ -> {:ok, false}
     end
   rescue
     _ -> {:ok, false}
   end
 
-  def check_dmarc(domain) do
+  def check_dmarc(domain, opts \\ []) do
     dmarc_domain = "_dmarc.#{domain}"
     
-    case :inet_res.lookup(String.to_charlist(dmarc_domain), :in, :txt) do
-      [] -> 
+    case lookup(dmarc_domain, :txt, opts) do
+      {:ok, []} -> 
         {:ok, %{error: "No DMARC record found"}}
-      records when is_list(records) -> 
-        # Convert the charlist to string and handle the nested list structure
+      {:ok, records} -> 
         dmarc_records = records
-        |> Enum.map(fn [record] -> 
-          record |> to_string() |> String.trim()
-        end)
+        |> Enum.map(&String.trim/1)
         |> Enum.filter(&String.starts_with?(&1, "v=DMARC1"))
 
         case dmarc_records do
           [] -> {:ok, %{error: "No valid DMARC record found"}}
           [record | _] -> {:ok, parse_dmarc_policy(record)}
         end
+      {:error, :nxdomain} -> 
+        {:ok, %{error: "No DMARC record found"}}
       {:error, reason} -> 
-        {:ok, %{error: "DNS lookup failed: #{reason}"}}
+        {:ok, %{error: "DNS lookup failed: #{inspect(reason)}"}}
     end
   end
 
   # Private Functions
 
   @doc false
-  defp lookup_a_records(domain) do
-    case :inet_res.lookup(String.to_charlist(domain), :in, :a) do
-      [] ->
+  defp lookup_a_records(domain, opts) do
+    case lookup(domain, :a, opts) do
+      {:ok, []} ->
         {:error, :no_records}
 
-      ips when is_list(ips) ->
-        {:ok, Enum.map(ips, &(:inet.ntoa(&1) |> to_string()))}
+      {:ok, ips} ->
+        {:ok, ips}
 
-      _ ->
-        {:error, :invalid_response}
+      {:error, :nxdomain} ->
+        {:error, :no_records}
+
+      {:error, reason} ->
+        {:error, reason}
     end
   end
 
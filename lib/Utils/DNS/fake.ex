@@ -0,0 +1,418 @@
+defmodule DomainTwistex.DNS.Fake do
+  @moduledoc """
+  In-memory `DomainTwistex.DNS.Resolver` that answers from a zone fixture,
+  for testing and replaying scans without live DNS.
+
+      zone = %{
+        "example.com" => %{a: ["192.0.2.1"], mx: [%{priority: 10, server: "mail.example.com"}]},
+        "www.example.com" => %{cname: "example.com"},
+        "*.wild.example" => %{a: "192.0.2.9"},
+        "broken.example" => :servfail
+      }
+
+      DomainTwistex.Twist.analyze_domain("example.com",
+        whois: false,
+        resolver: {DomainTwistex.DNS.Fake, zone: zone}
+      )
+
+  Options:
+
+    * `:zone` - the fixture: a map, or the path of a JSON fixture (`.json`,
+      as `save_recording/2` writes) or of a BIND-style zone file (any other
+      extension, see `parse_zone/2`). Scans read files once, when the
+      resolver is opened (`open/1`); other lookups read them every time
+    * `:origin` - origin of a zone file without `$ORIGIN`
+    * `:default` - the answer for names not in the zone (default: `:nxdomain`)
+    * `:delay` - milliseconds every lookup waits before answering, to
+      exercise scan timeouts (default: 0)
+    * `:recorder` - a recorder from `start_recorder/1`; lookups then go to
+      its live resolver instead and are recorded
+
+  Zone keys are names, with `*.` for wildcards. Values are maps of record
+  type (`:a`, `:aaaa`, `:cname`, `:mx`, `:ns`, `:txt`) to records in the
+  form of `t:DomainTwistex.DNS.Resolver.record/0`, a single record, or an
+  error for that type. A whole name can also answer an error: `:nxdomain`,
+  `:servfail`, `:refused` or `:timeout`. Names that exist without records
+  of a type answer `{:ok, []}`, and aliases are followed for other types.
+
+  ## Recording
+
+  A recorder wraps a live resolver and keeps every answer, so a scan can be
+  captured once and replayed deterministically:
+
+      {:ok, recorder} = DomainTwistex.DNS.Fake.start_recorder(DomainTwistex.DNS.Resolver.InetRes)
+      DomainTwistex.Twist.analyze_domain("example.com", resolver: {DomainTwistex.DNS.Fake, recorder: recorder})
+      DomainTwistex.DNS.Fake.save_recording(recorder, "test/fixtures/example.com.json")
+
+      DomainTwistex.Twist.analyze_domain("example.com",
+        resolver: {DomainTwistex.DNS.Fake, zone: "test/fixtures/example.com.json"}
+      )
+
+  The random names `DomainTwistex.DNS.has_wildcard/2` probes are recorded
+  as the wildcard of their parent, so replayed probes find them.
+  """
+
+  @behaviour DomainTwistex.DNS.Resolver
+
+  alias DomainTwistex.DNS.Resolver
+
+  @types %{"a" => :a, "aaaa" => :aaaa, "cname" => :cname, "mx" => :mx, "ns" => :ns, "txt" => :txt}
+  @errors [:nxdomain, :servfail, :refused, :timeout]
+  @max_aliases 8
+  # Names DomainTwistex.DNS.has_wildcard/2 makes up: 12 random bytes in hex
+  @wildcard_probe ~r/\A[0-9a-f]{24}\.(.+)\z/
+
+  @type zone :: %{String.t() => atom() | %{Resolver.record_type() => [Resolver.record()] | atom()}}
+
+  @impl true
+  def lookup(name, type, opts) do
+    case Keyword.get(opts, :delay, 0) do
+      0 -> :ok
+      delay -> Process.sleep(delay)
+    end
+
+    case Keyword.fetch(opts, :recorder) do
+      {:ok, recorder} -> record(recorder, name, type)
+      :error -> answer(zone(opts), name_key(name), type, Keyword.get(opts, :default, :nxdomain), 0)
+    end
+  end
+
+  @doc """
+  Reads a zone file given as `:zone` once, for the lookups of a scan.
+  """
+  @impl true
+  def open(opts) do
+    case Keyword.get(opts, :zone) do
+      path when is_binary(path) ->
+        Keyword.put(opts, :zone, load!(path, Keyword.take(opts, [:origin])))
+
+      _zone ->
+        opts
+    end
+  end
+
+  @impl true
+  def close(_opts), do: :ok
+
+  @doc """
+  Loads a zone fixture: a JSON file (`.json`) or a BIND-style zone file.
+
+  ## Parameters
+    * path - The fixture file
+    * opts - `:origin` for zone files without `$ORIGIN`
+  """
+  @spec load!(Path.t(), keyword()) :: zone()
+  def load!(path, opts \\ []) do
+    contents = File.read!(path)
+
+    case Path.extname(path) do
+      ".json" -> contents |> Jason.decode!() |> normalize()
+      _ -> parse_zone(contents, opts)
+    end
+  end
+
+  @doc """
+  Parses a BIND-style zone file (RFC 1035 master file format).
+
+  `$ORIGIN`, `@`, relative names, omitted owners, TTLs, classes, comments
+  and parenthesised records are understood. A, AAAA, CNAME, MX, NS and TXT
+  records are kept; other types, such as SOA, are skipped.
+
+  ## Examples
+
+      iex> DomainTwistex.DNS.Fake.parse_zone(\"""
+      ...> $ORIGIN example.com.
+      ...> @    3600 IN A     192.0.2.1
+      ...>           IN MX    10 mail
+      ...> www       IN CNAME @
+      ...> \""")
+      %{
+        "example.com" => %{a: ["192.0.2.1"], mx: [%{priority: 10, server: "mail.example.com"}]},
+        "www.example.com" => %{cname: ["example.com"]}
+      }
+  """
+  @spec parse_zone(String.t(), keyword()) :: zone()
+  def parse_zone(text, opts \\ []) do
+    origin = opts |> Keyword.get(:origin) |> then(&(&1 && name_key(&1)))
+
+    {zone, _state} =
+      text
+      |> logical_lines()
+      |> Enum.reduce({%{}, %{origin: origin, owner: nil}}, &zone_line/2)
+
+    zone
+  end
+
+  @doc """
+  Starts a recorder around a live resolver, a module or `{module, opts}`
+  (default: the configured resolver, see `DomainTwistex.DNS.Resolver.from_opts/1`).
+  """
+  @spec start_recorder(Resolver.t() | nil) :: {:ok, pid()}
+  def start_recorder(resolver \\ nil) do
+    live = Resolver.from_opts(if resolver, do: [resolver: resolver], else: [])
+    Agent.start_link(fn -> %{resolver: live, zone: %{}} end)
+  end
+
+  @doc """
+  Returns the zone a recorder has captured so far.
+  """
+  @spec recording(pid()) :: zone()
+  def recording(recorder), do: Agent.get(recorder, & &1.zone)
+
+  @doc """
+  Writes the zone a recorder has captured as a JSON fixture, with names and
+  types sorted so that fixtures diff cleanly.
+  """
+  @spec save_recording(pid(), Path.t()) :: :ok
+  def save_recording(recorder, path) do
+    json =
+      recorder
+      |> recording()
+      |> Enum.sort()
+      |> Enum.map(fn {name, entry} -> {name, encode_entry(entry)} end)
+      |> Jason.OrderedObject.new()
+      |> Jason.encode!(pretty: true)
+
+    File.write!(path, json <> "\n")
+  end
+
+  # Answers
+
+  defp answer(zone, name, type, default, aliases) do
+    case find(zone, name) do
+      nil ->
+        {:error, default}
+
+      error when is_atom(error) ->
+        {:error, error}
+
+      entry ->
+        case {Map.fetch(entry, type), Map.get(entry, :cname)} do
+          {{:ok, error}, _cname} when is_atom(error) -> {:error, error}
+          {{:ok, records}, _cname} -> {:ok, records}
+          {:error, [target | _]} when aliases < @max_aliases -> follow(zone, target, type, aliases)
+          {:error, _cname} -> {:ok, []}
+        end
+    end
+  end
+
+  # A dangling alias answers the CNAME alone, so no records of the type
+  defp follow(zone, target, type, aliases) do
+    case answer(zone, target, type, :nxdomain, aliases + 1) do
+      {:error, :nxdomain} -> {:ok, []}
+      answer -> answer
+    end
+  end
+
+  # The name itself, or else the closest wildcard above it
+  defp find(zone, name) do
+    case Map.fetch(zone, name) do
+      {:ok, entry} -> entry
+      :error -> find_wildcard(zone, String.split(name, ".", parts: 2))
+    end
+  end
+
+  defp find_wildcard(zone, [_label, parent]) do
+    Map.get_lazy(zone, "*." <> parent, fn -> find_wildcard(zone, String.split(parent, ".", parts: 2)) end)
+  end
+
+  defp find_wildcard(_zone, [_label]), do: nil
+
+  defp zone(opts) do
+    case Keyword.fetch!(opts, :zone) do
+      path when is_binary(path) -> load!(path, Keyword.take(opts, [:origin]))
+      zone when is_map(zone) -> normalize(zone)
+    end
+  end
+
+  # Recording
+
+  defp record(recorder, name, type) do
+    {resolver, resolver_opts} = Agent.get(recorder, & &1.resolver)
+    result = resolver.lookup(name, type, resolver_opts)
+    key = Regex.replace(@wildcard_probe, name_key(name), "*.\\1")
+
+    Agent.update(recorder, fn state ->
+      case result do
+        {:ok, records} -> put_answer(state, key, type, records)
+        {:error, :nxdomain} -> state
+        {:error, reason} when reason in @errors -> put_answer(state, key, type, reason)
+        {:error, _reason} -> put_answer(state, key, type, :servfail)
+      end
+    end)
+
+    result
+  end
+
+  defp put_answer(state, name, type, answer) do
+    update_in(state.zone, fn zone ->
+      Map.update(zone, name, %{type => answer}, fn
+        entry when is_map(entry) -> Map.put(entry, type, answer)
+        _error -> %{type => answer}
+      end)
+    end)
+  end
+
+  defp encode_entry(error) when is_atom(error), do: Atom.to_string(error)
+
+  defp encode_entry(entry) do
+    entry
+    |> Enum.sort()
+    |> Enum.map(fn
+      {type, error} when is_atom(error) -> {Atom.to_string(type), Atom.to_string(error)}
+      {type, records} -> {Atom.to_string(type), records}
+    end)
+    |> Jason.OrderedObject.new()
+  end
+
+  # Fixtures, from maps or decoded JSON
+
+  defp normalize(zone) do
+    Map.new(zone, fn {name, entry} -> {name_key(name), normalize_entry(entry)} end)
+  end
+
+  defp normalize_entry(entry) when is_map(entry) do
+    Map.new(entry, fn {type, value} ->
+      type = type_key(type)
+      {type, normalize_records(type, value)}
+    end)
+  end
+
+  defp normalize_entry(error), do: error_key(error)
+
+  defp normalize_records(type, records) when is_list(records), do: Enum.map(records, &normalize_record(type, &1))
+
+  defp normalize_records(type, value) do
+    case error_key(value) do
+      nil -> [normalize_record(type, value)]
+      error -> error
+    end
+  end
+
+  defp normalize_record(:mx, %{priority: priority, server: server}), do: %{priority: priority, server: name_key(server)}
+  defp normalize_record(:mx, %{"priority" => priority, "server" => server}), do: normalize_record(:mx, %{priority: priority, server: server})
+  defp normalize_record(:mx, {priority, server}), do: normalize_record(:mx, %{priority: priority, server: server})
+  defp normalize_record(type, host) when type in [:cname, :ns], do: name_key(host)
+  defp normalize_record(_type, value), do: value
+
+  defp type_key(type) when is_atom(type), do: type_key(Atom.to_string(type))
+  defp type_key(type), do: Map.fetch!(@types, String.downcase(type))
+
+  defp error_key(error) when error in @errors, do: error
+  defp error_key(error) when is_binary(error), do: Enum.find(@errors, &(Atom.to_string(&1) == error))
+  defp error_key(_value), do: nil
+
+  defp name_key(name), do: name |> to_string() |> String.downcase() |> String.trim_trailing(".")
+
+  # Zone files
+
+  # Lines without comments, with parenthesised records joined into one line
+  defp logical_lines(text) do
+    {lines, pending} =
+      text
+      |> String.split(~r/\r?\n/)
+      |> Enum.map(&strip_comment/1)
+      |> Enum.reduce({[], nil}, fn line, {lines, pending} ->
+        line = if pending, do: pending <> " " <> line, else: line
+
+        if open_parens(line) > 0,
+          do: {lines, line},
+          else: {[line | lines], nil}
+      end)
+
+    Enum.reverse(if pending, do: [pending | lines], else: lines)
+  end
+
+  defp strip_comment(line) do
+    [code] = Regex.run(~r/\A(?:[^;"]|"(?:[^"\\]|\\.)*")*/, line)
+    code
+  end
+
+  defp open_parens(line) do
+    unquoted = String.replace(line, ~r/"(?:[^"\\]|\\.)*"/, "")
+    length(String.split(unquoted, "(")) - length(String.split(unquoted, ")"))
+  end
+
+  defp tokens(line) do
+    for match <- Regex.scan(~r/"((?:[^"\\]|\\.)*)"|[^\s()"]+/, line) do
+      case match do
+        [_quoted, text] -> {:quoted, String.replace(text, ~r/\\(.)/, "\\1")}
+        [token] -> token
+      end
+    end
+  end
+
+  defp zone_line(line, {zone, state}) do
+    case {line, tokens(line)} do
+      {_line, []} ->
+        {zone, state}
+
+      {_line, ["$ORIGIN", origin | _]} ->
+        {zone, %{state | origin: name_key(origin)}}
+
+      {_line, ["$" <> _directive | _]} ->
+        {zone, state}
+
+      {<<c, _::binary>>, tokens} when c in [?\s, ?\t] ->
+        {add_record(zone, state, state.owner, tokens), state}
+
+      {_line, [owner | tokens]} ->
+        owner = absolute(owner, state.origin)
+        {add_record(zone, state, owner, tokens), %{state | owner: owner}}
+    end
+  end
+
+  defp add_record(_zone, _state, nil, _tokens), do: raise(ArgumentError, "zone record without an owner name")
+
+  defp add_record(zone, state, owner, tokens) do
+    case Enum.drop_while(tokens, &ttl_or_class?/1) do
+      [type | rdata] when is_binary(type) ->
+        case Map.fetch(@types, String.downcase(type)) do
+          {:ok, type} ->
+            record = zone_record(type, rdata, state.origin)
+            Map.update(zone, owner, %{type => [record]}, fn entry -> Map.update(entry, type, [record], &(&1 ++ [record])) end)
+
+          :error ->
+            zone
+        end
+
+      _ ->
+        zone
+    end
+  end
+
+  defp ttl_or_class?(token) when is_binary(token) do
+    token =~ ~r/\A(\d+[smhdw]?)+\z/i or String.upcase(token) in ["IN", "CH", "HS", "CS"]
+  end
+
+  defp ttl_or_class?(_token), do: false
+
+  defp zone_record(type, [address | _], _origin) when type in [:a, :aaaa] do
+    case :inet.parse_address(String.to_charlist(address)) do
+      {:ok, ip} -> ip |> :inet.ntoa() |> to_string()
+      {:error, _} -> raise ArgumentError, "invalid #{type} record address: #{inspect(address)}"
+    end
+  end
+
+  defp zone_record(type, [host | _], origin) when type in [:cname, :ns], do: absolute(host, origin)
+  defp zone_record(:mx, [priority, host | _], origin), do: %{priority: String.to_integer(priority), server: absolute(host, origin)}
+
+  defp zone_record(:txt, strings, _origin) do
+    Enum.map_join(strings, fn
+      {:quoted, text} -> text
+      text -> text
+    end)
+  end
+
+  defp zone_record(type, rdata, _origin), do: raise(ArgumentError, "invalid #{type} record: #{inspect(rdata)}")
+
+  defp absolute("@", origin), do: origin
+
+  defp absolute(name, origin) do
+    cond do
+      String.ends_with?(name, ".") -> name_key(name)
+      origin -> name_key(name) <> "." <> origin
+      true -> name_key(name)
+    end
+  end
+end
@@ -0,0 +1,75 @@
+defmodule DomainTwistex.DNS.Message do
+  @moduledoc """
+  DNS wire format (RFC 1035) for the resolvers that speak it themselves,
+  `DomainTwistex.DNS.Resolver.DoH` and `DomainTwistex.DNS.Resolver.DoT`.
+
+  Messages are encoded and decoded by OTP's `:inet_dns`, and answers are
+  turned into the records of `t:DomainTwistex.DNS.Resolver.record/0`.
+  """
+
+  alias DomainTwistex.DNS.Resolver
+
+  @rcodes %{1 => :formerr, 2 => :servfail, 3 => :nxdomain, 4 => :notimp, 5 => :refused}
+
+  @doc """
+  Encodes a recursive query for the records of one type.
+
+  ## Parameters
+    * name - The name to look up
+    * type - The record type
+    * id - The message id (default: 0, as RFC 8484 recommends for DoH)
+  """
+  @spec encode_query(String.t(), Resolver.record_type(), non_neg_integer()) :: binary()
+  def encode_query(name, type, id \\ 0) do
+    header = :inet_dns.make_header(id: id, opcode: :query, rd: true)
+    query = :inet_dns.make_dns_query(domain: String.to_charlist(name), type: type, class: :in)
+
+    :inet_dns.encode(:inet_dns.make_msg(header: header, qdlist: [query]))
+  end
+
+  @doc """
+  Decodes a response into the answers of the type asked for.
+
+  ## Returns
+    * `{:ok, [record]}` - The answers, `[]` when the name has none of the type
+    * `{:error, reason}` - The response code, such as `:nxdomain` or
+      `:servfail`, or `:invalid_response` when the message cannot be decoded
+
+  ## Examples
+
+      iex> alias DomainTwistex.DNS.Message
+      iex> Message.decode_response(<<0, 0, 0x81, 0x83, 0::64>>, :a)
+      {:error, :nxdomain}
+  """
+  @spec decode_response(binary(), Resolver.record_type()) :: {:ok, [Resolver.record()]} | {:error, Resolver.reason()}
+  def decode_response(response, type) do
+    case :inet_dns.decode(response) do
+      {:ok, msg} ->
+        case msg |> :inet_dns.msg(:header) |> :inet_dns.header(:rcode) do
+          0 -> {:ok, records(msg, type)}
+          rcode -> {:error, Map.get(@rcodes, rcode, {:rcode, rcode})}
+        end
+
+      {:error, _reason} ->
+        {:error, :invalid_response}
+    end
+  end
+
+  @doc """
+  Returns the answers of a decoded `:inet_dns` message that have the type
+  asked for, as records.
+  """
+  @spec records(term(), Resolver.record_type()) :: [Resolver.record()]
+  def records(msg, type) do
+    for rr <- :inet_dns.msg(msg, :anlist), :inet_dns.rr(rr, :type) == type do
+      record(type, :inet_dns.rr(rr, :data))
+    end
+  end
+
+  defp record(type, ip) when type in [:a, :aaaa], do: ip |> :inet.ntoa() |> to_string()
+  defp record(type, host) when type in [:cname, :ns], do: host_name(host)
+  defp record(:mx, {priority, server}), do: %{priority: priority, server: host_name(server)}
+  defp record(:txt, strings), do: strings |> Enum.map(&to_string/1) |> Enum.join()
+
+  defp host_name(host), do: host |> to_string() |> String.trim_trailing(".")
+end
@@ -0,0 +1,137 @@
+defmodule DomainTwistex.DNS.Resolver do
+  @moduledoc """
+  Behaviour for the DNS backends `DomainTwistex.DNS` queries.
+
+  Built-in resolvers:
+
+    * `DomainTwistex.DNS.Resolver.InetRes` - Erlang's `:inet_res`, with the
+      system configuration or explicit nameservers (default)
+    * `DomainTwistex.DNS.Resolver.DoH` - DNS-over-HTTPS (RFC 8484)
+    * `DomainTwistex.DNS.Resolver.DoT` - DNS-over-TLS (RFC 7858)
+    * `DomainTwistex.DNS.Fake` - answers from a zone fixture, for tests and
+      replaying recorded scans
+
+  A resolver is given as a module or as `{module, opts}`, in the `:resolver`
+  option of `DomainTwistex.Twist.analyze_domain/2` (and the other scan
+  functions) or in the application environment:
+
+      config :domaintwistex,
+        resolver: {DomainTwistex.DNS.Resolver.InetRes, nameservers: ["9.9.9.9"]}
+
+      DomainTwistex.Twist.analyze_domain("example.com",
+        resolver: {DomainTwistex.DNS.Resolver.DoH, url: "https://dns.quad9.net/dns-query"}
+      )
+
+  ## Implementing a resolver
+
+      defmodule MyApp.Resolver do
+        @behaviour DomainTwistex.DNS.Resolver
+
+        @impl true
+        def lookup(name, :a, _opts), do: {:ok, ["192.0.2.1"]}
+        def lookup(_name, _type, _opts), do: {:error, :nxdomain}
+      end
+
+  Resolvers that hold a connection implement `c:open/1` and `c:close/1`,
+  which scans call around their lookups (see `with_session/2`).
+  """
+
+  @type record_type :: :a | :aaaa | :cname | :mx | :ns | :txt
+
+  @typedoc """
+  An answer record, by type:
+
+    * `:a`, `:aaaa` - the address as a string, `"192.0.2.1"`
+    * `:cname`, `:ns` - the host name, without the root dot
+    * `:mx` - `%{priority: integer, server: host name}`
+    * `:txt` - the record's strings, concatenated
+  """
+  @type record :: String.t() | %{priority: non_neg_integer(), server: String.t()}
+
+  @typedoc """
+  Why a lookup failed. `:nxdomain` means the name does not exist; a name
+  without records of the type answers `{:ok, []}` instead.
+  """
+  @type reason :: :nxdomain | :servfail | :refused | :timeout | term()
+
+  @type t :: module() | {module(), keyword()}
+
+  @doc """
+  Looks up the records of one type for a name. Only answers of the type
+  asked for are returned: an `:a` lookup of an alias gives the addresses,
+  not the CNAME.
+  """
+  @callback lookup(name :: String.t(), type :: record_type(), opts :: keyword()) ::
+              {:ok, [record()]} | {:error, reason()}
+
+  @doc """
+  Opens what the resolver keeps for the length of a scan, such as a
+  connection, and returns the options its lookups then receive. Called by
+  `with_session/2`.
+  """
+  @callback open(opts :: keyword()) :: keyword()
+
+  @doc "Releases what `c:open/1` opened."
+  @callback close(opts :: keyword()) :: :ok
+
+  @optional_callbacks open: 1, close: 1
+
+  @default DomainTwistex.DNS.Resolver.InetRes
+
+  @doc """
+  Returns the `{module, opts}` resolver for the options: the `:resolver`
+  option, the `:resolver` application environment, or `:inet_res` with the
+  system configuration.
+
+  Raises `ArgumentError` when the resolver does not implement this
+  behaviour.
+
+  ## Examples
+
+      iex> DomainTwistex.DNS.Resolver.from_opts([])
+      {DomainTwistex.DNS.Resolver.InetRes, []}
+
+      iex> DomainTwistex.DNS.Resolver.from_opts(resolver: {DomainTwistex.DNS.Resolver.DoT, host: "dns.quad9.net"})
+      {DomainTwistex.DNS.Resolver.DoT, [host: "dns.quad9.net"]}
+  """
+  @spec from_opts(keyword()) :: {module(), keyword()}
+  def from_opts(opts) do
+    resolver = Keyword.get_lazy(opts, :resolver, fn -> Application.get_env(:domaintwistex, :resolver, @default) end)
+
+    {module, resolver_opts} =
+      case resolver do
+        {module, resolver_opts} when is_atom(module) and is_list(resolver_opts) -> {module, resolver_opts}
+        module when is_atom(module) -> {module, []}
+        other -> raise ArgumentError, ":resolver must be a module or {module, opts}, got: #{inspect(other)}"
+      end
+
+    unless Code.ensure_loaded?(module) and function_exported?(module, :lookup, 3) do
+      raise ArgumentError, "#{inspect(module)} does not implement DomainTwistex.DNS.Resolver"
+    end
+
+    {module, resolver_opts}
+  end
+
+  @doc """
+  Runs a scan with the resolver of `opts` open for its whole length: `fun`
+  receives the options with the opened resolver, which is closed when it
+  returns. Resolvers without `c:open/1` are passed through unchanged, as
+  are resolvers that are already open.
+  """
+  @spec with_session(keyword(), (keyword() -> result)) :: result when result: term()
+  def with_session(opts, fun) do
+    {module, resolver_opts} = from_opts(opts)
+    session_opts =
+      if function_exported?(module, :open, 1), do: module.open(resolver_opts), else: resolver_opts
+
+    if session_opts == resolver_opts do
+      fun.(opts)
+    else
+      try do
+        fun.(Keyword.put(opts, :resolver, {module, session_opts}))
+      after
+        module.close(session_opts)
+      end
+    end
+  end
+end
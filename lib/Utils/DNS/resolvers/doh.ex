@@ -0,0 +1,54 @@
+defmodule DomainTwistex.DNS.Resolver.DoH do
+  @moduledoc """
+  Resolves over DNS-over-HTTPS (RFC 8484): each query is POSTed in wire
+  format, as `application/dns-message`, with Req.
+
+  Options:
+
+    * `:url` - the DoH endpoint (default: `"https://cloudflare-dns.com/dns-query"`),
+      e.g. `"https://dns.quad9.net/dns-query"` or `"https://dns.google/dns-query"`
+    * `:timeout` - per-query timeout in milliseconds (default: 5000)
+    * `:req_options` - extra options for `Req.post/2`, such as
+      `connect_options` for a proxy or a private CA
+  """
+
+  @behaviour DomainTwistex.DNS.Resolver
+
+  alias DomainTwistex.DNS.Message
+
+  @default_url "https://cloudflare-dns.com/dns-query"
+  @default_timeout 5_000
+  @content_type "application/dns-message"
+
+  @impl true
+  def lookup(name, type, opts) do
+    req_options =
+      Keyword.merge(
+        [
+          body: Message.encode_query(name, type),
+          headers: [{"content-type", @content_type}, {"accept", @content_type}],
+          receive_timeout: Keyword.get(opts, :timeout, @default_timeout),
+          decode_body: false,
+          retry: false
+        ],
+        Keyword.get(opts, :req_options, [])
+      )
+
+    case Req.post(Keyword.get(opts, :url, @default_url), req_options) do
+      {:ok, %Req.Response{status: 200, body: body}} when is_binary(body) ->
+        Message.decode_response(body, type)
+
+      {:ok, %Req.Response{status: status}} ->
+        {:error, {:http_status, status}}
+
+      {:error, %Req.TransportError{reason: :timeout}} ->
+        {:error, :timeout}
+
+      {:error, %Req.TransportError{reason: reason}} ->
+        {:error, reason}
+
+      {:error, exception} ->
+        {:error, Exception.message(exception)}
+    end
+  end
+end
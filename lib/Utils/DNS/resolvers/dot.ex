@@ -0,0 +1,198 @@
+defmodule DomainTwistex.DNS.Resolver.DoT do
+  @moduledoc """
+  Resolves over DNS-over-TLS (RFC 7858): queries are sent in wire format,
+  length-prefixed, to port 853. The server certificate is verified against
+  the OS trust store.
+
+  A TLS handshake costs more than the query it carries, so scans keep one
+  connection to the server open for all their lookups (RFC 7858 §3.4):
+  `DomainTwistex.DNS.Resolver.with_session/2` starts it with `open/1`, and
+  queries are pipelined on it and matched to their answers by message ID.
+  The connection is opened on first use and again after the server closes
+  it. Lookups outside a session, such as a single `DomainTwistex.DNS.lookup/3`,
+  open a connection of their own for each query.
+
+  Options:
+
+    * `:host` - the DoT server (default: `"one.one.one.one"`), e.g.
+      `"dns.quad9.net"`, or an IP address together with `:server_name`
+    * `:server_name` - the name the certificate must match (default: `:host`)
+    * `:port` - the port (default: 853)
+    * `:timeout` - per-query timeout in milliseconds (default: 5000)
+    * `:ssl_options` - extra options for `:ssl.connect/4`, such as `cacertfile`
+      for a private CA
+  """
+
+  @behaviour DomainTwistex.DNS.Resolver
+
+  use GenServer
+
+  alias DomainTwistex.DNS.Message
+
+  @default_host "one.one.one.one"
+  @default_port 853
+  @default_timeout 5_000
+
+  @impl DomainTwistex.DNS.Resolver
+  def lookup(name, type, opts) do
+    timeout = Keyword.get(opts, :timeout, @default_timeout)
+
+    case Keyword.fetch(opts, :connection) do
+      {:ok, connection} ->
+        GenServer.call(connection, {:query, name, type, timeout}, :infinity)
+
+      :error ->
+        case connect(opts, false) do
+          {:ok, socket} ->
+            try do
+              with :ok <- :ssl.send(socket, Message.encode_query(name, type, :rand.uniform(0xFFFF))),
+                   {:ok, response} <- :ssl.recv(socket, 0, timeout) do
+                Message.decode_response(response, type)
+              end
+            after
+              :ssl.close(socket)
+            end
+
+          {:error, reason} ->
+            {:error, reason}
+        end
+    end
+  end
+
+  @doc """
+  Starts the process that holds the connection to the server of `opts`,
+  linked to the caller, and returns the options with it as `:connection`.
+  """
+  @impl DomainTwistex.DNS.Resolver
+  def open(opts) do
+    if Keyword.has_key?(opts, :connection) do
+      opts
+    else
+      {:ok, connection} = GenServer.start_link(__MODULE__, opts)
+      Keyword.put(opts, :connection, connection)
+    end
+  end
+
+  @doc """
+  Closes the connection `open/1` started.
+  """
+  @impl DomainTwistex.DNS.Resolver
+  def close(opts), do: GenServer.stop(Keyword.fetch!(opts, :connection))
+
+  # Connection process
+
+  @impl GenServer
+  def init(opts), do: {:ok, %{opts: opts, socket: nil, pending: %{}}}
+
+  @impl GenServer
+  def handle_call({:query, name, type, timeout}, from, state) do
+    case ensure_connected(state) do
+      {:ok, state} ->
+        id = free_id(state.pending)
+
+        case :ssl.send(state.socket, Message.encode_query(name, type, id)) do
+          :ok ->
+            timer = Process.send_after(self(), {:query_timeout, id}, timeout)
+            {:noreply, put_in(state.pending[id], {from, type, timer})}
+
+          {:error, reason} ->
+            {:reply, {:error, reason}, disconnect(state, reason)}
+        end
+
+      {:error, reason} ->
+        {:reply, {:error, reason}, state}
+    end
+  end
+
+  @impl GenServer
+  def handle_info({:ssl, socket, <<id::16, _rest::binary>> = response}, %{socket: socket} = state) do
+    case Map.pop(state.pending, id) do
+      {{from, type, timer}, pending} ->
+        Process.cancel_timer(timer)
+        GenServer.reply(from, Message.decode_response(response, type))
+        {:noreply, %{state | pending: pending}}
+
+      {nil, _pending} ->
+        {:noreply, state}
+    end
+  end
+
+  def handle_info({:ssl_closed, socket}, %{socket: socket} = state) do
+    {:noreply, disconnect(state, :closed)}
+  end
+
+  def handle_info({:ssl_error, socket, reason}, %{socket: socket} = state) do
+    {:noreply, disconnect(state, reason)}
+  end
+
+  def handle_info({:query_timeout, id}, state) do
+    case Map.pop(state.pending, id) do
+      {{from, _type, _timer}, pending} ->
+        GenServer.reply(from, {:error, :timeout})
+        {:noreply, %{state | pending: pending}}
+
+      {nil, _pending} ->
+        {:noreply, state}
+    end
+  end
+
+  # Messages of a connection that has since been replaced
+  def handle_info(_message, state), do: {:noreply, state}
+
+  defp ensure_connected(%{socket: nil} = state) do
+    with {:ok, socket} <- connect(state.opts, true), do: {:ok, %{state | socket: socket}}
+  end
+
+  defp ensure_connected(state), do: {:ok, state}
+
+  # Fails the queries still waiting on the connection; the next query
+  # connects again
+  defp disconnect(state, reason) do
+    if state.socket, do: :ssl.close(state.socket)
+
+    for {_id, {from, _type, timer}} <- state.pending do
+      Process.cancel_timer(timer)
+      GenServer.reply(from, {:error, reason})
+    end
+
+    %{state | socket: nil, pending: %{}}
+  end
+
+  defp free_id(pending) do
+    id = :rand.uniform(0xFFFF)
+    if Map.has_key?(pending, id), do: free_id(pending), else: id
+  end
+
+  defp connect(opts, active) do
+    host = Keyword.get(opts, :host, @default_host)
+
+    # `packet: 2` frames each message with the two-byte length DNS over TCP uses
+    ssl_options =
+      Keyword.merge(
+        [
+          mode: :binary,
+          packet: 2,
+          active: active,
+          verify: :verify_peer,
+          cacerts: :public_key.cacerts_get(),
+          server_name_indication: String.to_charlist(Keyword.get(opts, :server_name, host)),
+          customize_hostname_check: [match_fun: :public_key.pkix_verify_hostname_match_fun(:https)]
+        ],
+        Keyword.get(opts, :ssl_options, [])
+      )
+
+    :ssl.connect(
+      address(host),
+      Keyword.get(opts, :port, @default_port),
+      ssl_options,
+      Keyword.get(opts, :timeout, @default_timeout)
+    )
+  end
+
+  defp address(host) do
+    case :inet.parse_address(String.to_charlist(host)) do
+      {:ok, ip} -> ip
+      {:error, _} -> String.to_charlist(host)
+    end
+  end
+end
@@ -0,0 +1,70 @@
+defmodule DomainTwistex.DNS.Resolver.InetRes do
+  @moduledoc """
+  Resolves with Erlang's `:inet_res`, over UDP with TCP fallback.
+
+  Without options the system resolver configuration is used. Options:
+
+    * `:nameservers` - upstreams to query instead, as `"9.9.9.9"`,
+      `"9.9.9.9:5353"`, `"[2620:fe::fe]:53"` or `{ip_tuple, port}`
+    * `:timeout` - per-query timeout in milliseconds (default: 5000)
+
+  Passing nameservers bypasses a filtering system resolver:
+
+      DomainTwistex.Twist.analyze_domain("example.com",
+        resolver: {DomainTwistex.DNS.Resolver.InetRes, nameservers: ["9.9.9.9", "149.112.112.112"]}
+      )
+  """
+
+  @behaviour DomainTwistex.DNS.Resolver
+
+  alias DomainTwistex.DNS.Message
+
+  @default_port 53
+  @default_timeout 5_000
+
+  @impl true
+  def lookup(name, type, opts) do
+    res_opts = opts |> Keyword.get(:nameservers) |> res_opts()
+
+    case :inet_res.resolve(String.to_charlist(name), :in, type, res_opts, Keyword.get(opts, :timeout, @default_timeout)) do
+      {:ok, msg} -> {:ok, Message.records(msg, type)}
+      {:error, {reason, _msg}} -> {:error, reason}
+      {:error, reason} -> {:error, reason}
+    end
+  end
+
+  @doc """
+  Parses a nameserver into the `{ip, port}` form `:inet_res` takes.
+
+  ## Examples
+
+      iex> DomainTwistex.DNS.Resolver.InetRes.nameserver("9.9.9.9")
+      {{9, 9, 9, 9}, 53}
+
+      iex> DomainTwistex.DNS.Resolver.InetRes.nameserver("[2620:fe::fe]:5353")
+      {{9760, 254, 0, 0, 0, 0, 0, 254}, 5353}
+
+      iex> DomainTwistex.DNS.Resolver.InetRes.nameserver("[2620:fe::fe]")
+      {{9760, 254, 0, 0, 0, 0, 0, 254}, 53}
+  """
+  @spec nameserver(String.t() | {:inet.ip_address(), :inet.port_number()}) :: {:inet.ip_address(), :inet.port_number()}
+  def nameserver({ip, port} = nameserver) when is_tuple(ip) and is_integer(port), do: nameserver
+
+  def nameserver(address) when is_binary(address) do
+    {host, port} =
+      case Regex.run(~r/\A\[(.+)\]:(\d+)\z|\A\[(.+)\]\z|\A([^:]+):(\d+)\z/, address) do
+        [_, ipv6, port] -> {ipv6, String.to_integer(port)}
+        [_, "", "", ipv6] -> {ipv6, @default_port}
+        [_, "", "", "", ipv4, port] -> {ipv4, String.to_integer(port)}
+        nil -> {address, @default_port}
+      end
+
+    case :inet.parse_address(String.to_charlist(host)) do
+      {:ok, ip} -> {ip, port}
+      {:error, _} -> raise ArgumentError, "invalid nameserver address: #{inspect(address)}"
+    end
+  end
+
+  defp res_opts(nil), do: []
+  defp res_opts(nameservers), do: [nameservers: Enum.map(nameservers, &nameserver/1)]
+end
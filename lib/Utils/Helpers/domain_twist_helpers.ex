@@ -1,5 +1,6 @@
 defmodule DomainTwistex.Utils do
   alias DomainTwistex.DNS
+  alias DomainTwistex.Permutate.Keyboard
   alias DomainTwistex.SPF
   alias DomainTwistex.Utils.Whois
 
@@ -12,10 +13,11 @@ defmodule DomainTwistex.Utils do
   @doc """
   Generates domain permutations using the pure Elixir Permutate module.
 
-  Produces 18 permutation types: Addition, Bitsquatting, Hyphenation,
+  Produces 24 permutation types: Addition, Bitsquatting, Hyphenation,
   HyphenationTldBoundary, Insertion, Omission, Repetition, Replacement,
-  Subdomain, Transposition, VowelSwap, VowelShuffle, DoubleVowelInsertion,
-  Keyword, Tld, FauxTld, Mapped, and Homoglyph.
+  Subdomain, Various, Transposition, VowelSwap, VowelShuffle, DoubleVowelInsertion,
+  Keyword, Tld, TldTypo, FauxTld, Mapped, Morphological, Homoglyph, Cyrillic, Phonetic,
+  and LayoutSwitch.
 
   ## Parameters
     * domain - String representing the domain to generate permutations for
@@ -23,16 +25,28 @@ defmodule DomainTwistex.Utils do
       * :faux_tld - include FauxTld permutations (default: false, adds ~14K entries)
       * :double_vowel - include DoubleVowelInsertion (default: true)
       * :vowel_shuffle - include VowelShuffle (default: true)
+      * :vowel_shuffle_budget - most VowelShuffle candidates (default: 1000),
+        sampled by :vowel_shuffle_seed (default: 0) beyond it
+      * :only - list of kind names to generate (e.g. ["Homoglyph", "Tld", "Keyword"])
+      * :except - list of kind names to skip
+      * :max_per_kind - cap per kind, an integer or a map of kind => limit
+      * :depth - 1 (default) or 2 to add ranked two-kind combinations, whose
+        kind is the chain applied (e.g. ["Homoglyph", "Tld"])
+      * :budget - maximum number of depth-2 candidates (default: 1000)
+      * :pairs - kind pairs to combine at depth 2, e.g. [{"Keyword", "Omission"}]
+      * :layouts - keyboard layouts for Insertion and Replacement, e.g. [:qwertz, :azerty]
 
   ## Returns
-    List of generated domain permutation maps with :fqdn, :tld, and :kind keys
+    List of generated domain permutation maps with :fqdn, :unicode, :tld, :kind, :kinds and :edit keys.
+    `:fqdn` is the ASCII (punycode) form used for resolution, `:unicode` the display form.
+    `:kinds` lists every kind producing the domain and `:kind` is the primary one.
 
   ## Examples
       ```
       iex(1)> DomainTwistex.Utils.generate_permutations("google.com")
       [
-        %{kind: "Keyword", fqdn: "servicegoogle.com", tld: "com"},
-        %{kind: "Homoglyph", fqdn: "ğöögle.com", tld: "com"},
+        %{kind: "Keyword", kinds: ["Keyword"], fqdn: "servicegoogle.com", unicode: "servicegoogle.com", tld: "com", ...},
+        %{kind: "Homoglyph", kinds: ["Homoglyph"], fqdn: "xn--ggle-5qa.com", unicode: "gögle.com", tld: "com", ...},
         # ...
       ]
       ```
@@ -46,6 +60,28 @@ defmodule DomainTwistex.Utils do
   """
   defdelegate generate_permutations(domain, opts), to: DomainTwistex.Permutate
 
+  @doc """
+  Lazily generates deduplicated domain permutations.
+
+  See `DomainTwistex.Permutate.stream/2`.
+  """
+  defdelegate stream_permutations(domain, opts \\ []), to: DomainTwistex.Permutate, as: :stream
+
+  @doc """
+  Computes the UTS #39 confusable skeleton of a domain.
+
+  See `DomainTwistex.Permutate.Confusables.skeleton/1`.
+  """
+  defdelegate skeleton(domain), to: DomainTwistex.Permutate.Confusables
+
+  @doc """
+  Validates a host name against RFC 1035 and RFC 5891, returning `:ok` or
+  `{:error, {reason, label}}`.
+
+  See `DomainTwistex.Permutate.Hostname.validate/1`.
+  """
+  defdelegate validate_hostname(name), to: DomainTwistex.Permutate.Hostname, as: :validate
+
   @doc """
   Validates and resolves domain information while checking for TLD-related issues.
 
@@ -76,8 +112,8 @@ defmodule DomainTwistex.Utils do
     "172.28.", "172.29.", "172.30.", "172.31."
   ]
 
-  def validate_domain_resolution(domain, tld) do
-    case DNS.resolve_ips(domain) do
+  def validate_domain_resolution(domain, tld, opts \\ []) do
+    case DNS.resolve_ips(domain, opts) do
       {:ok, %{ips: ips, cname: cname}} ->
         # Classify IPs safely
         {public_ips, internal_ips} = try do
@@ -134,7 +170,12 @@ defmodule DomainTwistex.Utils do
   Performs comprehensive domain validation checks including DNS and server availability.
 
   ## Parameters
-    * permutation - Map containing at least :fqdn and :tld keys
+    * permutation - Map containing at least :fqdn and :tld keys. Other keys
+      (:kind, :kinds, :edit, ...) are kept in the result
+    * domain - The original domain, for fuzzy scores
+    * opts - `:whois` to include WHOIS/RDAP data, `:layouts` for the
+      keyboard proximity score (see `DomainTwistex.Permutate.Keyboard`), and
+      `:resolver` for the DNS lookups (see `DomainTwistex.DNS.Resolver`)
 
   ## Returns
     * `{:ok, map}` - Successfully checked domain with all information
@@ -159,15 +200,17 @@ defmodule DomainTwistex.Utils do
     include_whois = Keyword.get(opts, :whois, false)
 
     # A-record-first approach (like dnstwist) - fast, finds active domains
-    case validate_domain_resolution(permutation.fqdn, permutation.tld) do
+    case validate_domain_resolution(permutation.fqdn, permutation.tld, opts) do
       {:ok, %{ips: ips, public_ips: public_ips, internal_ips: internal_ips, flags: ip_flags}} ->
         # Run all independent DNS lookups concurrently
+        dns_opts = Keyword.take(opts, [:resolver])
+
         dns_tasks = [
-          Task.async(fn -> {:mx, safe_dns_query(fn -> DNS.get_mx_records(permutation.fqdn) end, [])} end),
-          Task.async(fn -> {:txt, safe_dns_query(fn -> DNS.get_txt_records(permutation.fqdn) end, [])} end),
-          Task.async(fn -> {:dmarc, safe_dns_query(fn -> DNS.check_dmarc(permutation.fqdn) end, %{})} end),
-          Task.async(fn -> {:ns, safe_dns_query(fn -> DNS.get_nameservers(permutation.fqdn) end, [])} end),
-          Task.async(fn -> {:wildcard, safe_dns_query(fn -> DNS.has_wildcard(permutation.fqdn) end, false)} end)
+          Task.async(fn -> {:mx, safe_dns_query(fn -> DNS.get_mx_records(permutation.fqdn, dns_opts) end, [])} end),
+          Task.async(fn -> {:txt, safe_dns_query(fn -> DNS.get_txt_records(permutation.fqdn, dns_opts) end, [])} end),
+          Task.async(fn -> {:dmarc, safe_dns_query(fn -> DNS.check_dmarc(permutation.fqdn, dns_opts) end, %{})} end),
+          Task.async(fn -> {:ns, safe_dns_query(fn -> DNS.get_nameservers(permutation.fqdn, dns_opts) end, [])} end),
+          Task.async(fn -> {:wildcard, safe_dns_query(fn -> DNS.has_wildcard(permutation.fqdn, dns_opts) end, false)} end)
         ]
 
         dns_results = Task.await_many(dns_tasks, 10_000)
@@ -183,7 +226,7 @@ defmodule DomainTwistex.Utils do
         # Skip HTTP check if no public IPs - don't connect to localhost/private ranges
         server_response = if public_ips != [] do
           try do
-            check_server(permutation.fqdn)
+            check_server(permutation.fqdn, hd(public_ips))
           rescue
             _ -> %{status: :error, reason: "check failed"}
           catch
@@ -214,9 +257,14 @@ defmodule DomainTwistex.Utils do
           nil
         end
 
-        # Fuzzy matching scores
+        # Fuzzy matching scores (on the display form, not the punycode)
         fuzzy = try do
-          calculate_fuzzy_scores(domain, permutation.fqdn)
+          calculate_fuzzy_scores(
+            domain,
+            Map.get(permutation, :unicode, permutation.fqdn),
+            Map.get(permutation, :edit),
+            opts
+          )
         rescue
           _ -> %{}
         catch
@@ -257,18 +305,36 @@ defmodule DomainTwistex.Utils do
     * :levenshtein - Edit distance (lower = more similar)
     * :keyboard_distance - Weighted distance accounting for keyboard proximity
     * :visual_similarity - Score for visually similar characters (homoglyphs)
+    * :confusable - true when both domains share a UTS #39 skeleton
+
+  When the permutation's `edit` is given (see `DomainTwistex.Permutate.Edit`),
+  the scores use it instead of re-deriving the change: the Levenshtein
+  distance of a local edit is that of its original and replacement
+  substrings, and an edit made with a neighbouring key scores on the layout
+  it was made on.
+
+  Without an edit, `:keyboard_proximity` is the best score over the layouts
+  in the `:layouts` option (default QWERTY, QWERTZ and AZERTY), each scaled
+  by its weight (see `DomainTwistex.Permutate.Keyboard`).
   """
-  def calculate_fuzzy_scores(original, permuted) do
+  def calculate_fuzzy_scores(original, permuted, edit \\ nil, opts \\ []) do
     # Extract domain name without TLD for comparison
     orig_name = original |> String.split(".") |> List.first()
     perm_name = permuted |> String.split(".") |> List.first()
 
+    levenshtein =
+      case edit do
+        %{position: _, original: from, replacement: to} -> levenshtein_distance(from, to)
+        _ -> levenshtein_distance(orig_name, perm_name)
+      end
+
     %{
       jaro_winkler: String.jaro_distance(original, permuted),
-      levenshtein: levenshtein_distance(orig_name, perm_name),
-      levenshtein_normalized: normalized_levenshtein(orig_name, perm_name),
+      levenshtein: levenshtein,
+      levenshtein_normalized: normalized_levenshtein(orig_name, perm_name, levenshtein),
       char_diff: count_char_differences(orig_name, perm_name),
-      keyboard_proximity: keyboard_proximity_score(orig_name, perm_name)
+      keyboard_proximity: keyboard_proximity_score(orig_name, perm_name, edit, opts),
+      confusable: DomainTwistex.Permutate.Confusables.confusable?(original, permuted)
     }
   end
 
@@ -302,12 +368,12 @@ defmodule DomainTwistex.Utils do
   end
 
   # Normalized Levenshtein (0.0-1.0, higher = more similar)
-  defp normalized_levenshtein(s1, s2) do
+  defp normalized_levenshtein(s1, s2, distance) do
     max_len = max(String.length(s1), String.length(s2))
     if max_len == 0 do
       1.0
     else
-      1.0 - levenshtein_distance(s1, s2) / max_len
+      max(0.0, 1.0 - distance / max_len)
     end
   end
 
@@ -325,42 +391,49 @@ defmodule DomainTwistex.Utils do
   end
 
   # Keyboard proximity score - lower distance for adjacent keys
-  defp keyboard_proximity_score(original, permuted) do
-    # QWERTY keyboard layout - adjacent keys get lower penalty
-    keyboard_rows = [
-      ~w(q w e r t y u i o p),
-      ~w(a s d f g h j k l),
-      ~w(z x c v b n m)
-    ]
-
-    key_positions =
-      keyboard_rows
-      |> Enum.with_index()
-      |> Enum.flat_map(fn {row, row_idx} ->
-        row |> Enum.with_index() |> Enum.map(fn {key, col_idx} -> {key, {row_idx, col_idx}} end)
-      end)
-      |> Map.new()
+  defp keyboard_proximity_score(original, permuted, %{layout: layout} = edit, opts) do
+    # The edit used a neighbouring key on its own layout: score that one key
+    # (as below) plus the length penalty for insertions
+    weight = opts |> Keyboard.layouts() |> Keyword.get(layout, 1.0)
+    len = max(String.length(original), 1)
+    len_diff = abs(String.length(original) - String.length(permuted))
+
+    distance =
+      case edit do
+        %{original: <<c1::utf8>>, replacement: <<c2::utf8>>} -> Keyboard.distance(layout, c1, c2) || 1.0
+        _ -> 1.0
+      end
+
+    weight * max(0.0, 1.0 - distance / 5.0 / len - len_diff * 0.1)
+  end
+
+  defp keyboard_proximity_score(original, permuted, _edit, opts) do
+    # Best score over the selected layouts, scaled by their weights
+    opts
+    |> Keyboard.layouts()
+    |> Enum.map(fn {layout, weight} -> weight * layout_proximity_score(original, permuted, layout) end)
+    |> Enum.max()
+  end
 
-    orig_chars = String.downcase(original) |> String.graphemes()
-    perm_chars = String.downcase(permuted) |> String.graphemes()
+  defp layout_proximity_score(original, permuted, layout) do
+    orig_chars = original |> String.downcase() |> String.to_charlist()
+    perm_chars = permuted |> String.downcase() |> String.to_charlist()
 
     # Compare character by character for same-length portions
     min_len = min(length(orig_chars), length(perm_chars))
 
     distances =
       Enum.zip(Enum.take(orig_chars, min_len), Enum.take(perm_chars, min_len))
-      |> Enum.map(fn {c1, c2} ->
-        if c1 == c2 do
+      |> Enum.map(fn
+        {c, c} ->
           0.0
-        else
-          case {Map.get(key_positions, c1), Map.get(key_positions, c2)} do
-            {nil, _} -> 1.0
-            {_, nil} -> 1.0
-            {{r1, c1_pos}, {r2, c2_pos}} ->
-              # Euclidean distance on keyboard
-              :math.sqrt(:math.pow(r1 - r2, 2) + :math.pow(c1_pos - c2_pos, 2)) / 5.0
+
+        {c1, c2} ->
+          # Euclidean distance on the keyboard, 1.0 for keys not on it
+          case Keyboard.distance(layout, c1, c2) do
+            nil -> 1.0
+            distance -> distance / 5.0
           end
-        end
       end)
 
     # Add penalty for length difference
@@ -392,6 +465,8 @@ defmodule DomainTwistex.Utils do
 
   ## Parameters
     * domain - String representing the domain to check
+    * address - IP address to connect to, e.g. one resolved by the scan's
+      resolver (default: the domain, looked up by the system resolver)
 
   ## Returns
     * map containing server response information or error details
@@ -406,9 +481,9 @@ defmodule DomainTwistex.Utils do
       }
       ```
   """
-  def check_server(domain) do
+  def check_server(domain, address \\ nil) do
     case :gen_tcp.connect(
-           String.to_charlist(domain),
+           String.to_charlist(address || domain),
            80,
            [:binary, packet: 0, active: false],
            10000
@@ -12,9 +12,10 @@ defmodule DomainTwistex.Twist do
   @doc """
   Analyzes a domain by generating permutations and checking them concurrently.
 
-  Generates all permutations for the given domain, then resolves each one with
-  parallel DNS, WHOIS, and server checks. Filters out the original domain and
-  wildcard-only results (wildcard + no public IPs).
+  Streams permutations for the given domain (see `DomainTwistex.Permutate.stream/2`)
+  and resolves each one as it is generated, with parallel DNS, WHOIS, and server
+  checks. Filters out the original domain and wildcard-only results
+  (wildcard + no public IPs).
 
   ## Parameters
     * domain - String representing the base domain to analyze (e.g., "example.com")
@@ -23,13 +24,31 @@ defmodule DomainTwistex.Twist do
       * :timeout - Timeout in milliseconds for each task (default: 15000)
       * :ordered - Whether to maintain permutation order in results (default: false)
       * :whois - Enable WHOIS/RDAP lookups (default: true)
+      * :order_by - `:generation` (default) resolves candidates as they are
+        generated; `:likelihood` scores them first (see
+        `DomainTwistex.Permutate.Likelihood`) and resolves the riskiest first.
+        Ranking needs the full candidate set before resolution starts. Results
+        carry the score under `:likelihood`; pass `ordered: true` to keep them
+        in that order
+      * :top_n - Only resolve this many candidates, the first ones in the
+        chosen order (e.g. the 500 most likely)
+      * :resolver - DNS backend for every lookup of the scan, a module or
+        `{module, opts}` (default: :inet_res with the system configuration).
+        See `DomainTwistex.DNS.Resolver` for :inet_res with explicit
+        nameservers, DNS-over-HTTPS and DNS-over-TLS
+      * Permutation options such as :only, :except, :max_per_kind, :depth,
+        :budget, :pairs, :layouts, :faux_tld, :double_vowel, :vowel_shuffle,
+        :vowel_shuffle_budget, :vowel_shuffle_seed and :generators are passed to
+        `DomainTwistex.Permutate.stream/2`
 
   ## Returns
     A map with the following keys:
       * :domain - The original domain string
       * :original - Resolved baseline data for the original domain (without fuzzy scores)
       * :permutations - List of resolvable permutation results (excluding wildcards with no public IPs)
-      * :stats - Map with :total (permutations generated) and :resolvable (permutations that resolved)
+      * :stats - Map with :total (permutations generated), :resolvable (permutations that resolved)
+        and :skipped (candidates left out by a generator budget, by kind; see
+        `DomainTwistex.Permutate.skipped/2`)
 
   ## Examples
       ```elixir
@@ -38,14 +57,28 @@ defmodule DomainTwistex.Twist do
         domain: "example.com",
         original: %{fqdn: "example.com", resolvable: true, ...},
         permutations: [
-          %{kind: "Tld", fqdn: "example.co.uk", ip_addresses: [...], ...},
+          %{kind: "Tld", kinds: ["Tld"], fqdn: "example.co.uk", ip_addresses: [...], ...},
           ...
         ],
-        stats: %{total: 9541, resolvable: 42}
+        stats: %{total: 9541, resolvable: 42, skipped: %{}}
       }
 
       # With custom options
       iex> DomainTwistex.Twist.analyze_domain("example.com", max_concurrency: 50, whois: true)
+
+      # Quick triage: only a few kinds, capped
+      iex> DomainTwistex.Twist.analyze_domain("example.com",
+      ...>   only: ["Homoglyph", "Tld", "Keyword"],
+      ...>   max_per_kind: %{"Tld" => 500}
+      ...> )
+
+      # Time-boxed scan of the riskiest candidates
+      iex> DomainTwistex.Twist.analyze_domain("example.com", order_by: :likelihood, top_n: 500)
+
+      # Resolve through specific upstreams rather than the system resolver
+      iex> DomainTwistex.Twist.analyze_domain("example.com",
+      ...>   resolver: {DomainTwistex.DNS.Resolver.InetRes, nameservers: ["9.9.9.9"]}
+      ...> )
       ```
 
   ## Performance Considerations
@@ -65,44 +98,76 @@ defmodule DomainTwistex.Twist do
         opts
       )
 
-    check_opts = [whois: opts[:whois]]
+    validate_order!(opts)
+    DomainTwistex.DNS.Resolver.with_session(opts, &scan(domain, &1))
+  end
+
+  defp scan(domain, opts) do
+    check_opts = [whois: opts[:whois]] ++ Keyword.take(opts, [:layouts, :resolver])
 
     # Resolve original domain and store its baseline data
     original = resolve_original(domain, check_opts)
 
-    all_permutations = Utils.generate_permutations(domain)
-    total_generated = length(all_permutations)
+    # Permutations are generated lazily and counted as they are consumed,
+    # so resolution starts before generation has finished (unless they are
+    # ranked by likelihood first)
+    generated = :counters.new(1, [])
 
     permutations =
-      all_permutations
-      |> Task.async_stream(
-        fn permutation -> Utils.check_domain(permutation, domain, check_opts) end,
-        ordered: opts[:ordered],
-        max_concurrency: opts[:max_concurrency],
-        timeout: opts[:timeout],
-        on_timeout: :kill_task
-      )
-      |> Stream.filter(fn
-        {:ok, {:ok, result}} ->
-          result.fqdn != domain and not (result.wildcard == true and result.public_ips == [])
-        _ -> false
-      end)
-      |> Stream.map(fn {:ok, {:ok, result}} -> result end)
-      |> Enum.into([])
+      domain
+      |> Utils.stream_permutations(opts)
+      |> Stream.each(fn _permutation -> :counters.add(generated, 1, 1) end)
+      |> prioritize(domain, opts)
+      |> analyze_chunk(domain, opts)
 
     %{
       domain: domain,
       original: original,
       permutations: permutations,
       stats: %{
-        total: total_generated,
-        resolvable: length(permutations)
+        total: total(generated, domain, opts),
+        resolvable: length(permutations),
+        skipped: DomainTwistex.Permutate.skipped(domain, opts)
       }
     }
   end
 
+  # `top_n` in generation order stops generation after that many
+  # candidates; the total still counts every candidate of the domain
+  defp total(generated, domain, opts) do
+    case {Keyword.get(opts, :order_by, :generation), Keyword.get(opts, :top_n)} do
+      {:generation, top_n} when is_integer(top_n) ->
+        domain |> Utils.stream_permutations(opts) |> Enum.count()
+
+      _ ->
+        :counters.get(generated, 1)
+    end
+  end
+
+  defp prioritize(permutations, domain, opts) do
+    case {Keyword.get(opts, :order_by, :generation), Keyword.get(opts, :top_n)} do
+      {:likelihood, _top_n} -> DomainTwistex.Permutate.Likelihood.rank(permutations, domain, opts)
+      {:generation, nil} -> permutations
+      {:generation, top_n} -> Stream.take(permutations, top_n)
+    end
+  end
+
+  defp validate_order!(opts) do
+    case Keyword.get(opts, :order_by, :generation) do
+      order when order in [:generation, :likelihood] -> :ok
+      other -> raise ArgumentError, ":order_by must be :generation or :likelihood, got: #{inspect(other)}"
+    end
+
+    case Keyword.get(opts, :top_n) do
+      nil -> :ok
+      top_n when is_integer(top_n) and top_n > 0 -> :ok
+      other -> raise ArgumentError, ":top_n must be a positive integer, got: #{inspect(other)}"
+    end
+  end
+
   defp resolve_original(domain, check_opts) do
-    permutation = %{fqdn: domain, tld: domain |> String.split(".", parts: 2) |> List.last()}
+    {_subdomain, _label, suffix} = DomainTwistex.Permutate.parse_domain(domain)
+    permutation = %{fqdn: domain, tld: suffix}
 
     case Utils.check_domain(permutation, domain, check_opts) do
       {:ok, result} -> Map.delete(result, :fuzzy)
@@ -131,7 +196,7 @@ defmodule DomainTwistex.Twist do
         domain: "google.com",
         original: %{...},
         permutations: [%{kind: "Tld", mx_records: [%{priority: 0, server: "smtp.google.com"}], ...}],
-        stats: %{total: 9541, resolvable: 42, mx_count: 12}
+        stats: %{total: 9541, resolvable: 42, skipped: %{}, mx_count: 12}
       }
       ```
   """
@@ -152,15 +217,19 @@ defmodule DomainTwistex.Twist do
   @doc """
   Returns all permutations for a domain without checking them.
 
-  Useful for splitting work across nodes.
+  Useful for splitting work across nodes. Accepts the permutation options of
+  `DomainTwistex.Permutate.generate_permutations/2` (:only, :except,
+  :max_per_kind, ...).
 
   ## Example
       iex> perms = DomainTwistex.Twist.get_permutations("example.com")
       iex> length(perms)
       4523
+
+      iex> DomainTwistex.Twist.get_permutations("example.com", only: ["Homoglyph"])
   """
-  def get_permutations(domain) do
-    Utils.generate_permutations(domain)
+  def get_permutations(domain, opts \\ []) do
+    Utils.generate_permutations(domain, opts)
   end
 
   @doc """
@@ -169,6 +238,7 @@ defmodule DomainTwistex.Twist do
   ## Parameters
     * domain - The domain to generate permutations for
     * num_chunks - Number of chunks (typically = number of nodes)
+    * opts - Permutation options (see `get_permutations/2`)
 
   ## Returns
     List of {chunk_index, permutations} tuples
@@ -179,10 +249,11 @@ defmodule DomainTwistex.Twist do
       3
       iex> {index, perms} = hd(chunks)
   """
-  def split_for_nodes(domain, num_chunks) do
-    domain
-    |> Utils.generate_permutations()
-    |> Enum.chunk_every(ceil(length(Utils.generate_permutations(domain)) / num_chunks))
+  def split_for_nodes(domain, num_chunks, opts \\ []) do
+    permutations = Utils.generate_permutations(domain, opts)
+
+    permutations
+    |> Enum.chunk_every(max(ceil(length(permutations) / num_chunks), 1))
     |> Enum.with_index()
     |> Enum.map(fn {chunk, idx} -> {idx, chunk} end)
   end
@@ -193,7 +264,8 @@ defmodule DomainTwistex.Twist do
   Use this on each node to process its assigned chunk.
 
   ## Parameters
-    * permutations - List of permutation maps to check
+    * permutations - Enumerable of permutation maps to check, either a chunk
+      from `split_for_nodes/2` or a lazy `DomainTwistex.Permutate.stream/2`
     * domain - Original domain (for distance calculation)
     * opts - Same options as analyze_domain/2
 
@@ -202,6 +274,11 @@ defmodule DomainTwistex.Twist do
       chunks = DomainTwistex.Twist.split_for_nodes("abbvie.com", 3)
       {0, my_chunk} = Enum.at(chunks, 0)
       results = DomainTwistex.Twist.analyze_chunk(my_chunk, "abbvie.com")
+
+      # Or resolve a lazy stream directly:
+      "abbvie.com"
+      |> DomainTwistex.Permutate.stream(vowel_shuffle: false)
+      |> DomainTwistex.Twist.analyze_chunk("abbvie.com")
   """
   def analyze_chunk(permutations, domain, opts \\ []) do
     opts =
@@ -215,7 +292,11 @@ defmodule DomainTwistex.Twist do
         opts
       )
 
-    check_opts = [whois: opts[:whois]]
+    DomainTwistex.DNS.Resolver.with_session(opts, &check_all(permutations, domain, &1))
+  end
+
+  defp check_all(permutations, domain, opts) do
+    check_opts = [whois: opts[:whois]] ++ Keyword.take(opts, [:layouts, :resolver])
 
     permutations
     |> Task.async_stream(
@@ -265,8 +346,8 @@ defmodule DomainTwistex.Twist do
       raise "No nodes available for distributed analysis"
     end
 
-    chunks = split_for_nodes(domain, num_nodes)
     chunk_opts = Keyword.drop(opts, [:nodes])
+    chunks = split_for_nodes(domain, num_nodes, chunk_opts)
 
     tasks =
       chunks
@@ -0,0 +1,28 @@
+defmodule DomainTwistex do
+  @moduledoc """
+  Domain permutation and typosquatting detection.
+
+  Most functionality lives in dedicated modules:
+
+    * `DomainTwistex.Twist` - permutation generation plus DNS analysis
+    * `DomainTwistex.Permutate` - pure permutation generation
+    * `DomainTwistex.Utils` - domain validation, fuzzy scoring and server checks
+
+  This module holds the entry points that work on a single suspicious domain.
+  """
+
+  @doc """
+  Explains how a candidate domain relates to a protected domain, e.g. a
+  domain seen in mail logs or an abuse report.
+
+  Returns `{:ok, matches}` with the permutation kinds and edits that turn
+  `protected` into `candidate`, or `:unrelated`. See
+  `DomainTwistex.Permutate.classify/3` for options and match details.
+
+  ## Examples
+
+      iex> DomainTwistex.classify("example.net", "example.com")
+      {:ok, [%{kind: "Tld", original: "com", replacement: "net"}]}
+  """
+  defdelegate classify(candidate, protected, opts \\ []), to: DomainTwistex.Permutate
+end
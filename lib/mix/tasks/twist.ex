@@ -14,12 +14,33 @@ defmodule Mix.Tasks.Twist do
       --mx-only               Only show domains with MX records
       -f, --format FORMAT     Output format: table, json, csv (default: table)
       -o, --output FILE       Write results to file
+      --only KINDS            Only generate these kinds (comma-separated, e.g. Homoglyph,Tld,Keyword)
+      --except KINDS          Skip these kinds (comma-separated)
+      --max-per-kind LIMIT    Cap candidates per kind: a number, or KIND=N pairs (e.g. Tld=200,Keyword=50)
+      --depth N               1 (default) or 2 to add two-kind combinations such as Homoglyph+Tld
+      --budget NUM            Maximum number of depth-2 candidates (default: 1000)
+      --layouts LAYOUTS       Keyboard layouts (comma-separated, e.g. qwertz,azerty; default: qwerty,qwertz,azerty)
+      --keywords SOURCE       Keyword packs (comma-separated, e.g. banking,crypto) or a keyword file path
+      --order-by ORDER        Resolution order: generation (default) or likelihood (riskiest first)
+      --top-n NUM             Only check the first NUM candidates in that order
+      --keyword-joins JOINS   Keyword joins (comma-separated: hyphen, none, dot, underscore_to_hyphen)
+      --vowel-shuffle-budget NUM  Maximum number of VowelShuffle candidates, sampled beyond it (default: 1000)
+      --vowel-shuffle-seed NUM    Seed of the VowelShuffle sample (default: 0)
+      --resolver UPSTREAM     DNS upstream instead of the system resolver: nameservers (comma-separated,
+                              e.g. 9.9.9.9,1.1.1.1:53), a DoH URL (https://...) or a DoT server
+                              (tls://host[:port], tls://[2620:fe::fe]:853)
 
   ## Examples
 
       mix twist example.com
       mix twist -c 100 -w example.com
       mix twist --format json -o results.json example.com
+      mix twist --only Homoglyph,Tld,Keyword --max-per-kind Tld=500 example.com
+      mix twist --depth 2 --budget 500 example.com
+      mix twist --layouts qwertz,azerty example.de
+      mix twist --order-by likelihood --top-n 500 example.com
+      mix twist --only Keyword --keywords banking,./keywords.txt examplebank.com
+      mix twist --resolver https://dns.quad9.net/dns-query example.com
   """
 
   use Mix.Task
@@ -42,7 +63,20 @@ defmodule Mix.Tasks.Twist do
           whois: :boolean,
           format: :string,
           output: :string,
-          mx_only: :boolean
+          mx_only: :boolean,
+          only: :string,
+          except: :string,
+          max_per_kind: :string,
+          depth: :integer,
+          budget: :integer,
+          layouts: :string,
+          keywords: :string,
+          keyword_joins: :string,
+          order_by: :string,
+          top_n: :integer,
+          vowel_shuffle_budget: :integer,
+          vowel_shuffle_seed: :integer,
+          resolver: :string
         ],
         aliases: [
           h: :help,
@@ -81,12 +115,15 @@ defmodule Mix.Tasks.Twist do
     IO.puts("Concurrency: #{concurrency}")
     IO.puts("Timeout: #{timeout}ms")
     IO.puts("WHOIS: #{if include_whois, do: "enabled", else: "disabled"}")
+    IO.puts("Resolver: #{Keyword.get(opts, :resolver, "system")}")
     IO.puts(String.duplicate("=", 50))
 
     results = DomainTwistex.Twist.analyze_domain(domain,
-      max_concurrency: concurrency,
-      timeout: timeout,
-      whois: include_whois
+      [
+        max_concurrency: concurrency,
+        timeout: timeout,
+        whois: include_whois
+      ] ++ order_opts(opts) ++ resolver_opts(opts) ++ permutation_opts(opts)
     )
 
     permutations = if mx_only do
@@ -102,6 +139,11 @@ defmodule Mix.Tasks.Twist do
     IO.puts("#{IO.ANSI.green()}Scan complete!#{IO.ANSI.reset()}")
     IO.puts("Total permutations: #{results.stats.total}")
     IO.puts("Resolvable found: #{results.stats.resolvable}")
+
+    for {kind, count} <- Map.get(results.stats, :skipped, %{}) do
+      IO.puts("Skipped (#{kind} budget): #{count}")
+    end
+
     IO.puts(String.duplicate("=", 50))
 
     if length(permutations) > 0 do
@@ -117,16 +159,159 @@ defmodule Mix.Tasks.Twist do
     end
   end
 
+  # Likelihood order is kept in the results, riskiest first
+  defp order_opts(opts) do
+    order =
+      case Keyword.get(opts, :order_by) do
+        nil -> []
+        "generation" -> [order_by: :generation]
+        "likelihood" -> [order_by: :likelihood, ordered: true]
+        other -> Mix.raise("Unknown order #{inspect(other)}, expected generation or likelihood")
+      end
+
+    order ++ Keyword.take(opts, [:top_n])
+  end
+
+  defp resolver_opts(opts) do
+    case Keyword.get(opts, :resolver) do
+      nil ->
+        []
+
+      "https://" <> _ = url ->
+        [resolver: {DomainTwistex.DNS.Resolver.DoH, url: url}]
+
+      "tls://" <> server ->
+        [resolver: {DomainTwistex.DNS.Resolver.DoT, dot_server(server)}]
+
+      nameservers ->
+        nameservers = nameservers |> String.split(",", trim: true) |> Enum.map(&String.trim/1)
+        [resolver: {DomainTwistex.DNS.Resolver.InetRes, nameservers: nameservers}]
+    end
+  end
+
+  # IPv6 addresses take brackets when a port follows, as in
+  # tls://[2620:fe::fe]:853
+  defp dot_server(server) do
+    case Regex.run(~r/\A\[(.+)\](?::(\d+))?\z|\A([^:\[\]]+)(?::(\d+))?\z/, server) do
+      [_, ipv6] -> [host: ipv6]
+      [_, ipv6, port] -> [host: ipv6, port: String.to_integer(port)]
+      [_, "", "", host] -> [host: host]
+      [_, "", "", host, port] -> [host: host, port: String.to_integer(port)]
+      nil -> dot_ipv6(server)
+    end
+  end
+
+  defp dot_ipv6(server) do
+    case :inet.parse_ipv6strict_address(String.to_charlist(server)) do
+      {:ok, _ip} -> [host: server]
+      {:error, _} -> Mix.raise("Invalid --resolver #{inspect("tls://" <> server)}, expected tls://host[:port]")
+    end
+  end
+
+  defp permutation_opts(opts) do
+    [
+      only: opts |> Keyword.get(:only) |> parse_kinds(),
+      except: opts |> Keyword.get(:except) |> parse_kinds(),
+      max_per_kind: opts |> Keyword.get(:max_per_kind) |> parse_max_per_kind(),
+      depth: Keyword.get(opts, :depth),
+      budget: Keyword.get(opts, :budget),
+      layouts: opts |> Keyword.get(:layouts) |> parse_layouts(),
+      keywords: opts |> Keyword.get(:keywords) |> parse_keywords(),
+      keyword_joins: opts |> Keyword.get(:keyword_joins) |> parse_keyword_joins(),
+      vowel_shuffle_budget: Keyword.get(opts, :vowel_shuffle_budget),
+      vowel_shuffle_seed: Keyword.get(opts, :vowel_shuffle_seed)
+    ]
+    |> Enum.reject(fn {_key, value} -> is_nil(value) end)
+  end
+
+  defp parse_kinds(nil), do: nil
+
+  defp parse_kinds(kinds) do
+    kinds
+    |> String.split(",", trim: true)
+    |> Enum.map(&String.trim/1)
+  end
+
+  defp parse_layouts(nil), do: nil
+
+  defp parse_layouts(value) do
+    known = Map.new(DomainTwistex.Permutate.Keyboard.names(), &{Atom.to_string(&1), &1})
+
+    value
+    |> String.split(",", trim: true)
+    |> Enum.map(fn name ->
+      case Map.fetch(known, String.trim(name)) do
+        {:ok, layout} -> layout
+        :error -> Mix.raise("Unknown layout #{inspect(name)}, expected one of: #{Enum.join(Map.keys(known), ", ")}")
+      end
+    end)
+  end
+
+  defp parse_keywords(nil), do: nil
+
+  defp parse_keywords(value) do
+    packs = Map.new(DomainTwistex.Permutate.Keywords.packs(), &{Atom.to_string(&1), &1})
+
+    value
+    |> String.split(",", trim: true)
+    |> Enum.map(fn source ->
+      source = String.trim(source)
+
+      cond do
+        Map.has_key?(packs, source) -> Map.fetch!(packs, source)
+        File.regular?(source) -> {:file, source}
+        true -> Mix.raise("Unknown keyword pack or file #{inspect(source)}, packs: #{Enum.join(Map.keys(packs), ", ")}")
+      end
+    end)
+  end
+
+  defp parse_keyword_joins(nil), do: nil
+
+  defp parse_keyword_joins(value) do
+    joins = %{"hyphen" => :hyphen, "none" => :none, "dot" => :dot, "underscore_to_hyphen" => :underscore_to_hyphen}
+
+    value
+    |> String.split(",", trim: true)
+    |> Enum.map(fn join ->
+      case Map.fetch(joins, String.trim(join)) do
+        {:ok, join} -> join
+        :error -> Mix.raise("Unknown keyword join #{inspect(join)}, expected one of: #{Enum.join(Map.keys(joins), ", ")}")
+      end
+    end)
+  end
+
+  defp parse_max_per_kind(nil), do: nil
+
+  defp parse_max_per_kind(value) do
+    case Integer.parse(value) do
+      {limit, ""} ->
+        limit
+
+      _ ->
+        value
+        |> String.split(",", trim: true)
+        |> Map.new(fn pair ->
+          with [kind, limit] <- String.split(pair, "=", parts: 2),
+               {limit, ""} <- limit |> String.trim() |> Integer.parse() do
+            {String.trim(kind), limit}
+          else
+            _ -> Mix.raise("Invalid --max-per-kind entry #{inspect(pair)}, expected KIND=N")
+          end
+        end)
+    end
+  end
+
   defp output_table(results, output_file) do
-    sorted = Enum.sort_by(results, & &1.kind)
+    sorted = Enum.sort_by(results, &format_kind(&1.kind))
 
     lines = [
       String.pad_trailing("KIND", 15) <>
+        String.pad_trailing("ALSO", 25) <>
         String.pad_trailing("DOMAIN", 40) <>
         String.pad_trailing("IPs", 20) <>
         String.pad_trailing("FLAGS", 15) <>
         "MX",
-      String.duplicate("-", 110)
+      String.duplicate("-", 135)
     ]
 
     result_lines =
@@ -146,7 +331,8 @@ defmodule Mix.Tasks.Twist do
             [first | _] -> first.server |> String.slice(0, 25)
           end
 
-        String.pad_trailing(r.kind, 15) <>
+        String.pad_trailing(format_kind(r.kind), 15) <>
+          String.pad_trailing(format_other_kinds(r), 25) <>
           String.pad_trailing(r.fqdn, 40) <>
           String.pad_trailing(ips, 20) <>
           String.pad_trailing(flags, 15) <>
@@ -166,6 +352,18 @@ defmodule Mix.Tasks.Twist do
     IO.puts("\nTotal: #{length(results)} domains")
   end
 
+  # Depth-2 candidates carry the chain of kinds applied
+  defp format_kind(kinds) when is_list(kinds), do: Enum.join(kinds, "+")
+  defp format_kind(kind), do: kind
+
+  # Kinds other than the primary one that also produce the domain
+  defp format_other_kinds(r) do
+    r
+    |> Map.get(:kinds, [r.kind])
+    |> List.delete(r.kind)
+    |> Enum.map_join(",", &format_kind/1)
+  end
+
   defp output_json(results, output_file) do
     json = encode_json(results)
 
@@ -179,7 +377,7 @@ defmodule Mix.Tasks.Twist do
 
   defp output_csv(results, output_file) do
     headers =
-      "kind,fqdn,ip_addresses,public_ips,internal_ips,ip_flags,mx_records,nameservers,resolvable\n"
+      "kind,kinds,fqdn,ip_addresses,public_ips,internal_ips,ip_flags,mx_records,nameservers,resolvable\n"
 
     rows =
       Enum.map(results, fn r ->
@@ -189,8 +387,9 @@ defmodule Mix.Tasks.Twist do
         flags = r.ip_flags |> Enum.map(&Atom.to_string/1) |> Enum.join(";")
         mx = r.mx_records |> Enum.map(& &1.server) |> Enum.join(";")
         ns = Enum.join(r.nameservers, ";")
+        kinds = r |> Map.get(:kinds, [r.kind]) |> Enum.map_join(";", &format_kind/1)
 
-        "#{r.kind},#{r.fqdn},\"#{ips}\",\"#{public}\",\"#{internal}\",\"#{flags}\",\"#{mx}\",\"#{ns}\",#{r.resolvable}"
+        "#{format_kind(r.kind)},\"#{kinds}\",#{r.fqdn},\"#{ips}\",\"#{public}\",\"#{internal}\",\"#{flags}\",\"#{mx}\",\"#{ns}\",#{r.resolvable}"
       end)
       |> Enum.join("\n")
 
@@ -0,0 +1,73 @@
+defmodule Mix.Tasks.UpdateConfusables do
+  @moduledoc """
+  Fetches the latest Unicode confusables data (UTS #39).
+
+  ## Usage
+
+      mix update_confusables
+
+  This task downloads confusables.txt from unicode.org. The file maps every
+  character to the prototype it is visually confusable with and is used by
+  `DomainTwistex.Permutate.Confusables` for skeletons and by the Homoglyph
+  and Cyrillic generators.
+
+  The resulting data is saved to priv/confusables.txt and compiled into the
+  application at build time.
+
+  ## Source
+
+  Data is sourced from: https://www.unicode.org/Public/security/latest/confusables.txt
+  """
+
+  use Mix.Task
+
+  @shortdoc "Update Unicode confusables data from unicode.org"
+
+  @confusables_url "https://www.unicode.org/Public/security/latest/confusables.txt"
+  @output_path "priv/confusables.txt"
+
+  @impl Mix.Task
+  def run(_args) do
+    Mix.Task.run("app.start")
+
+    Mix.shell().info("Fetching Unicode confusables...")
+    Mix.shell().info("Source: #{@confusables_url}")
+
+    case fetch() do
+      {:ok, body} ->
+        # Ensure priv directory exists
+        File.mkdir_p!("priv")
+        File.write!(@output_path, body)
+        Mix.shell().info("Successfully updated #{count_mappings(body)} confusable mappings")
+        Mix.shell().info("Output: #{@output_path}")
+
+      {:error, reason} ->
+        Mix.shell().error("Failed to update confusables: #{inspect(reason)}")
+        exit({:shutdown, 1})
+    end
+  end
+
+  defp fetch do
+    case Req.get(@confusables_url, receive_timeout: 30_000) do
+      {:ok, %Req.Response{status: 200, body: body}} when is_binary(body) ->
+        # Guard against error pages replacing the data file
+        if count_mappings(body) > 0 do
+          {:ok, String.trim_leading(body, "﻿")}
+        else
+          {:error, "unexpected response body"}
+        end
+
+      {:ok, %Req.Response{status: status}} ->
+        {:error, "HTTP #{status}"}
+
+      {:error, reason} ->
+        {:error, reason}
+    end
+  end
+
+  defp count_mappings(body) do
+    body
+    |> String.split("\n")
+    |> Enum.count(&String.match?(&1, ~r/^[0-9A-F]{4,6}\s*;/))
+  end
+end
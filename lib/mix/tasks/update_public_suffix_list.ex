@@ -0,0 +1,75 @@
+defmodule Mix.Tasks.UpdatePublicSuffixList do
+  @moduledoc """
+  Fetches the latest Mozilla Public Suffix List.
+
+  ## Usage
+
+      mix update_public_suffix_list
+
+  This task downloads public_suffix_list.dat from publicsuffix.org, the
+  canonical location maintained by Mozilla. The list is used by
+  `DomainTwistex.Permutate.PublicSuffix` to split domains into subdomain,
+  registrable label and public suffix.
+
+  The resulting data is saved to priv/public_suffix_list.dat and compiled into
+  the application at build time.
+
+  ## Source
+
+  Data is sourced from: https://publicsuffix.org/list/public_suffix_list.dat
+  """
+
+  use Mix.Task
+
+  @shortdoc "Update the Public Suffix List from publicsuffix.org"
+
+  @public_suffix_list_url "https://publicsuffix.org/list/public_suffix_list.dat"
+  @output_path "priv/public_suffix_list.dat"
+
+  @impl Mix.Task
+  def run(_args) do
+    Mix.Task.run("app.start")
+
+    Mix.shell().info("Fetching Public Suffix List...")
+    Mix.shell().info("Source: #{@public_suffix_list_url}")
+
+    case fetch() do
+      {:ok, body} ->
+        # Ensure priv directory exists
+        File.mkdir_p!("priv")
+        File.write!(@output_path, body)
+        Mix.shell().info("Successfully updated #{count_rules(body)} public suffix rules")
+        Mix.shell().info("Output: #{@output_path}")
+
+      {:error, reason} ->
+        Mix.shell().error("Failed to update Public Suffix List: #{inspect(reason)}")
+        exit({:shutdown, 1})
+    end
+  end
+
+  defp fetch do
+    case Req.get(@public_suffix_list_url, receive_timeout: 30_000) do
+      {:ok, %Req.Response{status: 200, body: body}} when is_binary(body) ->
+        # Guard against error pages or truncated downloads replacing the list
+        if String.contains?(body, "===BEGIN ICANN DOMAINS===") and
+             String.contains?(body, "===END PRIVATE DOMAINS===") do
+          {:ok, body}
+        else
+          {:error, "unexpected response body"}
+        end
+
+      {:ok, %Req.Response{status: status}} ->
+        {:error, "HTTP #{status}"}
+
+      {:error, reason} ->
+        {:error, reason}
+    end
+  end
+
+  defp count_rules(body) do
+    body
+    |> String.split("\n")
+    |> Enum.map(&String.trim/1)
+    |> Enum.count(&(&1 != "" and not String.starts_with?(&1, "//")))
+  end
+end
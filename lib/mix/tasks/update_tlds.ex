@@ -0,0 +1,143 @@
+defmodule Mix.Tasks.UpdateTlds do
+  @moduledoc """
+  Rebuilds the TLD list from the IANA root zone and the Public Suffix List.
+
+  ## Usage
+
+      mix update_tlds
+      mix update_tlds --output /var/lib/domaintwistex/tlds.txt
+
+  This task merges the delegated TLDs of the IANA root zone with the ICANN
+  section of the Public Suffix List (wildcard and exception rules skipped),
+  converts `xn--` labels to Unicode, and writes one suffix per line, sorted.
+
+  The result is saved to priv/tlds.txt by default, which is compiled into the
+  application at build time. To update a deployed application without
+  recompiling, write it elsewhere with `--output` and point the
+  `:tlds_path` application environment at it (see
+  `DomainTwistex.Permutate.Data`).
+
+  Provenance (source URLs, their versions, entry counts and the date of the
+  update) is written next to the list, as `tlds.meta.json` for `tlds.txt`.
+
+  ## Sources
+
+  Data is sourced from:
+
+    * https://data.iana.org/TLD/tlds-alpha-by-domain.txt
+    * https://publicsuffix.org/list/public_suffix_list.dat
+  """
+
+  use Mix.Task
+
+  alias DomainTwistex.Permutate.IDNA
+
+  @shortdoc "Rebuild the TLD list from IANA and the Public Suffix List"
+
+  @iana_tlds_url "https://data.iana.org/TLD/tlds-alpha-by-domain.txt"
+  @public_suffix_list_url "https://publicsuffix.org/list/public_suffix_list.dat"
+  @output_path "priv/tlds.txt"
+
+  @impl Mix.Task
+  def run(args) do
+    {opts, _, _} = OptionParser.parse(args, strict: [output: :string], aliases: [o: :output])
+    output = Keyword.get(opts, :output, @output_path)
+
+    Mix.Task.run("app.start")
+
+    Mix.shell().info("Fetching TLD lists...")
+    Mix.shell().info("Sources: #{@iana_tlds_url}, #{@public_suffix_list_url}")
+
+    with {:ok, iana} <- fetch(@iana_tlds_url, &parse_iana/1),
+         {:ok, psl} <- fetch(@public_suffix_list_url, &parse_public_suffix_list/1) do
+      tlds = (iana.entries ++ psl.entries) |> Enum.uniq() |> Enum.sort()
+
+      File.mkdir_p!(Path.dirname(output))
+      File.write!(output, Enum.join(tlds, "\n") <> "\n")
+      File.write!(meta_path(output), encode_meta(tlds, [{@iana_tlds_url, iana}, {@public_suffix_list_url, psl}]))
+
+      Mix.shell().info("Successfully updated #{length(tlds)} TLDs and public suffixes")
+      Mix.shell().info("Output: #{output}")
+    else
+      {:error, reason} ->
+        Mix.shell().error("Failed to update TLDs: #{inspect(reason)}")
+        exit({:shutdown, 1})
+    end
+  end
+
+  defp fetch(url, parse) do
+    case Req.get(url, receive_timeout: 30_000) do
+      {:ok, %Req.Response{status: 200, body: body}} when is_binary(body) ->
+        # Guard against error pages or truncated downloads replacing the list
+        case parse.(body) do
+          %{entries: []} -> {:error, "unexpected response body from #{url}"}
+          parsed -> {:ok, parsed}
+        end
+
+      {:ok, %Req.Response{status: status}} ->
+        {:error, "HTTP #{status} from #{url}"}
+
+      {:error, reason} ->
+        {:error, reason}
+    end
+  end
+
+  # "# Version 2024101600, Last Updated ..." then one uppercase TLD per line
+  defp parse_iana(body) do
+    lines = body |> String.split(~r/\R/) |> Enum.map(&String.trim/1)
+
+    version =
+      Enum.find_value(lines, fn
+        "# Version " <> version -> version
+        _ -> nil
+      end)
+
+    entries = for line <- lines, line != "", not String.starts_with?(line, "#"), do: to_unicode(line)
+
+    %{version: version, entries: entries}
+  end
+
+  # Rules between the ICANN markers; the PSL header carries `// VERSION:`
+  defp parse_public_suffix_list(body) do
+    lines = body |> String.split(~r/\R/) |> Enum.map(&String.trim/1)
+
+    version =
+      Enum.find_value(lines, fn
+        "// VERSION: " <> version -> version
+        _ -> nil
+      end)
+
+    entries =
+      lines
+      |> Enum.drop_while(&(not String.contains?(&1, "===BEGIN ICANN DOMAINS===")))
+      |> Enum.take_while(&(not String.contains?(&1, "===END ICANN DOMAINS===")))
+      |> Enum.reject(&(&1 == "" or String.starts_with?(&1, ["//", "*", "!"])))
+      |> Enum.map(&(&1 |> String.split() |> hd() |> to_unicode()))
+
+    %{version: version, entries: entries}
+  end
+
+  defp to_unicode(suffix) do
+    suffix = String.downcase(suffix)
+
+    case IDNA.to_unicode(suffix) do
+      {:ok, unicode} -> unicode
+      {:error, _} -> suffix
+    end
+  end
+
+  defp meta_path(output), do: Path.rootname(output) <> ".meta.json"
+
+  defp encode_meta(tlds, sources) do
+    meta = %{
+      generated_at: DateTime.utc_now() |> DateTime.truncate(:second) |> DateTime.to_iso8601(),
+      entries: length(tlds),
+      sources:
+        for {url, %{version: version, entries: entries}} <- sources do
+          %{url: url, version: version, entries: length(entries)}
+        end
+    }
+
+    Jason.encode!(meta, pretty: true) <> "\n"
+  end
+end
@@ -11,21 +11,22 @@ defmodule DomainTwistex.MixProject do
       elixir: "~> 1.17",
       start_permanent: Mix.env() == :prod,
       deps: deps(),
-      description: "Pure Elixir domain permutation and typosquatting detection engine. Generates 18 permutation types, resolves concurrently with DNS/WHOIS enrichment, and filters suspicious domains.",
+      description: "Pure Elixir domain permutation and typosquatting detection engine. Generates 24 permutation types, resolves concurrently with DNS/WHOIS enrichment, and filters suspicious domains.",
       package: package()
     ]
   end
 
   def application do
     [
-      extra_applications: [:logger]
+      extra_applications: [:logger, :ssl, :public_key]
     ]
   end
 
   defp deps do
     [
       {:req, "~> 0.5.16"},
-      {:ex_doc, "~> 0.39", only: :dev, runtime: false}
+      {:ex_doc, "~> 0.39", only: :dev, runtime: false},
+      {:benchee, "~> 1.3", only: :dev}
     ]
   end
 
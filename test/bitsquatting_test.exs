@@ -0,0 +1,56 @@
+defmodule DomainTwistex.Permutate.BitsquattingTest do
+  use ExUnit.Case
+
+  alias DomainTwistex.Permutate
+
+  # Bitsquatting candidates dnstwist generates for the registrable label
+  # (every in-place single-bit flip to a-z, 0-9 or `-`), less names starting
+  # or ending with a hyphen. twistrs inserts the flipped characters at every
+  # index of the fqdn instead, which gives 242 names for example.com.
+  @dnstwist_counts [
+    {"example.com", 33},
+    {"google.com", 27},
+    {"paypal.com", 26},
+    {"microsoft.com", 43},
+    {"amazon.co.uk", 24},
+    {"b2b-bank.de", 29}
+  ]
+
+  defp bitsquats(domain), do: Permutate.generate_permutations(domain, only: ["Bitsquatting"])
+
+  describe "reference domains" do
+    test "match dnstwist's counts" do
+      for {domain, count} <- @dnstwist_counts do
+        assert length(bitsquats(domain)) == count, domain
+      end
+    end
+
+    test "flip characters in place" do
+      for {domain, _count} <- @dnstwist_counts,
+          %{fqdn: fqdn, edit: edit} <- bitsquats(domain) do
+        assert String.length(fqdn) == String.length(domain), fqdn
+        assert <<_::utf8>> = edit.replacement
+      end
+
+      fqdns = Enum.map(bitsquats("google.com"), & &1.fqdn)
+      assert "foogle.com" in fqdns
+      assert "goggle.com" in fqdns
+      refute "gfoogle.com" in fqdns
+    end
+  end
+
+  describe "edits" do
+    test "record the bit flipped at each position" do
+      assert %{edit: %{position: 0, original: "e", replacement: "d", bit: 0}} =
+               Enum.find(bitsquats("example.com"), &(&1.fqdn == "dxample.com"))
+
+      assert %{edit: %{position: 3, original: "m", replacement: "-", bit: 6}} =
+               Enum.find(bitsquats("example.com"), &(&1.fqdn == "exa-ple.com"))
+    end
+
+    test "are recovered by classification" do
+      assert {:ok, matches} = DomainTwistex.classify("goggle.com", "google.com")
+      assert %{kind: "Bitsquatting", position: 2, original: "o", replacement: "g", bit: 3} in matches
+    end
+  end
+end
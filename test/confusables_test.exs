@@ -0,0 +1,43 @@
+defmodule DomainTwistex.Permutate.ConfusablesTest do
+  use ExUnit.Case
+
+  alias DomainTwistex.Permutate.Confusables
+
+  describe "skeleton/1" do
+    test "maps cross-script look-alikes to their Latin prototype" do
+      assert Confusables.skeleton("gооgle.net") == "google.net"
+      assert Confusables.skeleton("αpple.com") == Confusables.skeleton("apple.com")
+    end
+
+    test "decodes punycode and ignores case" do
+      assert Confusables.skeleton("xn--ggle-55da.com") == Confusables.skeleton("Google.COM")
+    end
+
+    test "maps ASCII look-alikes" do
+      assert Confusables.confusable?("paypa1.com", "paypal.com")
+      assert Confusables.confusable?("rnicrosoft.com", "microsoft.com")
+      assert Confusables.confusable?("exarnple.com", "example.com")
+      refute Confusables.confusable?("example.com", "example.net")
+    end
+  end
+
+  test "lookalikes/1 only returns lowercase, IDNA-stable characters" do
+    glyphs = Confusables.lookalikes(?o)
+
+    assert 0x043E in glyphs
+    assert 0x03BF in glyphs
+    refute 0xFF4F in glyphs
+    refute 0x041E in glyphs
+  end
+
+  test "homoglyph and cyrillic generators use confusables" do
+    results =
+      DomainTwistex.Permutate.generate_permutations("apple.com", only: ["Homoglyph", "Cyrillic"])
+
+    unicode = Enum.map(results, & &1.unicode)
+
+    assert "аpple.com" in unicode
+    assert "аррӏе.com" in unicode
+    assert Enum.find(results, &(&1.unicode == "аррӏе.com")).kind == "Cyrillic"
+  end
+end
@@ -1,6 +1,29 @@
+defmodule DomainTwistex.PermutateTest.BrandSuffix do
+  use DomainTwistex.Permutate.Generator
+
+  @impl true
+  def kind, do: "BrandSuffix"
+
+  @impl true
+  def generate({_subdomain, label, _suffix} = parts, opts) do
+    for suffix <- Keyword.get(opts, :brand_suffixes, ["hq"]) do
+      candidate(parts, label <> suffix, kind())
+    end
+  end
+
+  @impl true
+  def validate_opts(opts) do
+    if is_list(Keyword.get(opts, :brand_suffixes, [])),
+      do: :ok,
+      else: {:error, ":brand_suffixes must be a list"}
+  end
+end
+
 defmodule DomainTwistex.PermutateTest do
   use ExUnit.Case
 
+  alias DomainTwistex.PermutateTest.BrandSuffix
+
   describe "generate_permutations/1" do
     test "generates permutations for a simple domain" do
       results = DomainTwistex.Permutate.generate_permutations("test.com")
@@ -19,6 +42,7 @@ defmodule DomainTwistex.PermutateTest do
       [first | _] = DomainTwistex.Permutate.generate_permutations("example.com")
 
       assert Map.has_key?(first, :fqdn)
+      assert Map.has_key?(first, :unicode)
       assert Map.has_key?(first, :tld)
       assert Map.has_key?(first, :kind)
     end
@@ -41,7 +65,7 @@ defmodule DomainTwistex.PermutateTest do
         refute String.contains?(fqdn, "..")
         refute String.starts_with?(fqdn, ".")
         refute String.ends_with?(fqdn, ".")
-        refute String.contains?(fqdn, "--")
+        refute fqdn |> String.replace("xn--", "") |> String.contains?("--")
       end
     end
 
@@ -65,5 +89,647 @@ defmodule DomainTwistex.PermutateTest do
 
       assert fqdns == Enum.uniq(fqdns)
     end
+
+    test "homoglyphs are emitted in punycode with a unicode display form" do
+      results = DomainTwistex.Permutate.generate_permutations("google.com")
+      homoglyphs = Enum.filter(results, &(&1.kind == "Homoglyph"))
+
+      assert %{fqdn: "xn--ggle-5qa.com", unicode: "gögle.com"} =
+               Enum.find(homoglyphs, &(&1.unicode == "gögle.com"))
+
+      for %{fqdn: fqdn} <- homoglyphs do
+        assert DomainTwistex.Permutate.IDNA.ascii?(fqdn)
+      end
+    end
+
+    test "mutates the registrable label and keeps subdomain and suffix" do
+      results = DomainTwistex.Permutate.generate_permutations("login.example.co.uk")
+      fqdns = Enum.map(results, & &1.fqdn)
+
+      assert "login.exmaple.co.uk" in fqdns
+      assert "login.example.com" in fqdns
+      assert "login.examplea.co.uk" in fqdns
+
+      for %{fqdn: fqdn, kind: kind} <- results, kind not in ["Tld", "TldTypo", "Hyphenation", "Various"] do
+        assert String.starts_with?(fqdn, "login.")
+        assert String.ends_with?(fqdn, ".co.uk")
+      end
+    end
+  end
+
+  describe "generators" do
+    test "runs generators passed in the :generators option" do
+      results =
+        DomainTwistex.Permutate.generate_permutations("example.com",
+          generators: [BrandSuffix],
+          brand_suffixes: ["hq", "corp"]
+        )
+
+      brand = results |> Enum.filter(&(&1.kind == "BrandSuffix")) |> Enum.map(& &1.fqdn)
+      assert "examplehq.com" in brand
+      assert "examplecorp.com" in brand
+      assert "BrandSuffix" in DomainTwistex.Permutate.kinds(generators: [BrandSuffix])
+    end
+
+    test "raises on invalid options and non-generator modules" do
+      assert_raise ArgumentError, fn ->
+        DomainTwistex.Permutate.generate_permutations("example.com", faux_tld: "yes")
+      end
+
+      assert_raise ArgumentError, fn ->
+        DomainTwistex.Permutate.generate_permutations("example.com",
+          generators: [BrandSuffix],
+          brand_suffixes: "hq"
+        )
+      end
+
+      assert_raise ArgumentError, fn ->
+        DomainTwistex.Permutate.generate_permutations("example.com", generators: [String])
+      end
+    end
+  end
+
+  describe "stream/2" do
+    test "emits the same candidates as generate_permutations/2" do
+      assert Enum.to_list(DomainTwistex.Permutate.stream("test.com")) ==
+               DomainTwistex.Permutate.generate_permutations("test.com")
+    end
+
+    test "is lazy and deduplicates across generators" do
+      assert [%{kind: "Addition"}, %{kind: "Addition"}] =
+               "example.com" |> DomainTwistex.Permutate.stream() |> Enum.take(2)
+
+      fqdns =
+        "google.com"
+        |> DomainTwistex.Permutate.stream(faux_tld: true)
+        |> Enum.map(& &1.fqdn)
+
+      assert fqdns == Enum.uniq(fqdns)
+    end
+  end
+
+  describe "kind selection" do
+    test ":only and :except select kinds" do
+      only =
+        DomainTwistex.Permutate.generate_permutations("example.com",
+          only: ["Homoglyph", "Tld", "Keyword", "FauxTld"]
+        )
+
+      assert only |> Enum.map(& &1.kind) |> MapSet.new() ==
+               MapSet.new(["Homoglyph", "Tld", "Keyword", "FauxTld"])
+
+      except = DomainTwistex.Permutate.generate_permutations("example.com", except: ["Tld"])
+      refute Enum.any?(except, &(&1.kind == "Tld"))
+      assert Enum.any?(except, &(&1.kind == "Addition"))
+    end
+
+    test ":max_per_kind caps unique candidates per kind" do
+      results =
+        DomainTwistex.Permutate.generate_permutations("example.com",
+          only: ["Tld", "Addition"],
+          max_per_kind: %{"Tld" => 10}
+        )
+
+      assert Enum.count(results, &(&1.kind == "Tld")) == 10
+      assert Enum.count(results, &("Addition" in &1.kinds)) == 26
+
+      capped = DomainTwistex.Permutate.generate_permutations("example.com", max_per_kind: 3)
+      assert capped |> Enum.frequencies_by(& &1.kind) |> Map.values() |> Enum.all?(&(&1 <= 3))
+    end
+
+    test "unknown kinds raise" do
+      assert_raise ArgumentError, fn ->
+        DomainTwistex.Permutate.generate_permutations("example.com", only: ["Typo"])
+      end
+    end
+  end
+
+  describe "classify/3" do
+    test "explains single-technique squats" do
+      assert DomainTwistex.classify("exmaple.com", "example.com") ==
+               {:ok, [%{kind: "Transposition", position: 2, original: "am", replacement: "ma"}]}
+
+      assert {:ok, matches} = DomainTwistex.classify("example-login.com", "example.com")
+      assert %{kind: "Keyword", keyword: "login", placement: :suffix, separator: "-", category: "credential"} in matches
+
+      assert {:ok, matches} = DomainTwistex.classify("login.example.net", "login.example.co.uk")
+      assert %{kind: "Tld", original: "co.uk", replacement: "net"} in matches
+    end
+
+    test "decodes punycode candidates" do
+      assert {:ok, matches} = DomainTwistex.classify("xn--ggle-5qa.com", "google.com")
+      assert %{kind: "Homoglyph", position: 1, original: "o", replacement: "ö"} in matches
+    end
+
+    test "returns :unrelated for unrelated and identical domains" do
+      assert DomainTwistex.classify("unrelated.org", "example.com") == :unrelated
+      assert DomainTwistex.classify("example.com", "example.com") == :unrelated
+    end
+
+    test "recognises every generated candidate" do
+      "example.com"
+      |> DomainTwistex.Permutate.stream(max_per_kind: 50)
+      |> Stream.reject(&(&1.fqdn == "example.com" or &1.fqdn != String.downcase(&1.fqdn)))
+      |> Enum.each(fn candidate ->
+        assert {:ok, matches} = DomainTwistex.classify(candidate.fqdn, "example.com")
+        assert candidate.kind in Enum.map(matches, & &1.kind), inspect(candidate)
+      end)
+    end
+
+    test "falls back to generation for generators without classify/3" do
+      assert {:ok, matches} =
+               DomainTwistex.classify("examplehq.com", "example.com", generators: [BrandSuffix])
+
+      assert %{kind: "BrandSuffix"} in matches
+      assert DomainTwistex.classify("examplehq.com", "example.com") == :unrelated
+    end
+  end
+
+  describe "depth 2" do
+    test "appends ranked two-kind combinations within the budget" do
+      results =
+        DomainTwistex.Permutate.generate_permutations("example.com",
+          only: ["Addition"],
+          depth: 2,
+          pairs: [{"Homoglyph", "Tld"}],
+          budget: 20
+        )
+
+      {single, double} = Enum.split_with(results, &is_binary(&1.kind))
+
+      assert Enum.all?(single, &(&1.kind == "Addition"))
+      assert length(double) == 20
+      assert Enum.all?(double, &(&1.kind == ["Homoglyph", "Tld"]))
+      assert results |> Enum.map(& &1.fqdn) |> Enum.uniq() |> length() == length(results)
+    end
+
+    test "the budget keeps the highest-weighted pairs" do
+      results =
+        DomainTwistex.Permutate.generate_permutations("example.com",
+          only: [],
+          depth: 2,
+          pairs: [{"Keyword", "Tld", 0.1}, {"Homoglyph", "Tld", 1.0}],
+          budget: 10
+        )
+
+      assert length(results) == 10
+      assert Enum.all?(results, &(&1.kind == ["Homoglyph", "Tld"]))
+    end
+
+    test "invalid depth options raise" do
+      assert_raise ArgumentError, fn ->
+        DomainTwistex.Permutate.generate_permutations("example.com", depth: 3)
+      end
+
+      assert_raise ArgumentError, fn ->
+        DomainTwistex.Permutate.generate_permutations("example.com", depth: 2, pairs: [{"Typo", "Tld"}])
+      end
+
+      assert_raise ArgumentError, fn ->
+        DomainTwistex.Permutate.generate_permutations("example.com", depth: 2, budget: 0)
+      end
+    end
+  end
+
+  describe "edit provenance" do
+    test "candidates record the edit that produced them" do
+      results = DomainTwistex.Permutate.generate_permutations("example.com")
+
+      assert %{kind: "Replacement", edit: %{position: 0, original: "e", replacement: "w", layout: :qwerty}} =
+               Enum.find(results, &(&1.fqdn == "wxample.com"))
+
+      assert %{edit: %{keyword: "login", placement: :suffix, separator: "-"}} =
+               Enum.find(results, &(&1.fqdn == "example-login.com"))
+
+      assert %{edit: %{original: "com", replacement: "net"}} = Enum.find(results, &(&1.fqdn == "example.net"))
+      assert Enum.all?(results, &is_map(&1.edit))
+    end
+
+    test "depth-2 candidates carry one edit per kind" do
+      [candidate | _] =
+        DomainTwistex.Permutate.generate_permutations("example.com",
+          only: [],
+          depth: 2,
+          pairs: [{"Homoglyph", "Tld"}],
+          budget: 5
+        )
+
+      assert [%{position: _, original: _, replacement: _}, %{original: "com", replacement: _}] = candidate.edit
+    end
+
+    test "fuzzy scores use the edit's layout" do
+      edit = %{position: 0, original: "t", replacement: "z", layout: :qwertz}
+      with_edit = DomainTwistex.Utils.calculate_fuzzy_scores("test.com", "zest.com", edit, layouts: [:qwerty])
+      without = DomainTwistex.Utils.calculate_fuzzy_scores("test.com", "zest.com", nil, layouts: [:qwerty])
+
+      assert with_edit.levenshtein == 1
+      assert with_edit.keyboard_proximity > without.keyboard_proximity
+    end
+
+    test "describes edits in plain words" do
+      alias DomainTwistex.Permutate.Edit
+
+      assert Edit.describe(%{position: 3, original: "m", replacement: ""}) == "`m` omitted at index 3"
+      assert Edit.describe([%{position: 1, original: "o", replacement: "ö"}, %{original: "com", replacement: "net"}]) ==
+               "`ö` substituted for `o` at index 1, then `com` replaced with `net`"
+    end
+  end
+
+  describe "multi-kind attribution" do
+    test "duplicates are merged into kinds with a priority-based primary kind" do
+      results = DomainTwistex.Permutate.generate_permutations("example.com")
+
+      # Addition runs first, but Repetition takes priority
+      assert %{kind: "Repetition", kinds: ["Repetition", "Addition"], edit: %{position: 7, replacement: "e"}} =
+               Enum.find(results, &(&1.fqdn == "examplee.com"))
+
+      assert Enum.all?(results, &(&1.kind == hd(&1.kinds)))
+      assert results |> Enum.map(& &1.fqdn) |> Enum.uniq() |> length() == length(results)
+    end
+
+    test "only selected kinds are attributed" do
+      results = DomainTwistex.Permutate.generate_permutations("example.com", except: ["Repetition"])
+
+      assert %{kind: "Addition", kinds: ["Addition"]} = Enum.find(results, &(&1.fqdn == "examplee.com"))
+    end
+
+    test ":max_per_kind counts primary kinds" do
+      results = DomainTwistex.Permutate.generate_permutations("example.com", max_per_kind: %{"Repetition" => 1})
+
+      assert Enum.count(results, &(&1.kind == "Repetition")) == 1
+    end
+  end
+
+  describe "keyboard layouts" do
+    test ":layouts selects the layouts Replacement uses" do
+      qwertz = DomainTwistex.Permutate.generate_permutations("test.com", only: ["Replacement"], layouts: [:qwertz])
+      fqdns = Enum.map(qwertz, & &1.fqdn)
+
+      assert "zest.com" in fqdns
+      refute "yest.com" in fqdns
+      assert Enum.all?(qwertz, &(&1.edit.layout == :qwertz))
+
+      dvorak = DomainTwistex.Permutate.generate_permutations("test.com", only: ["Replacement"], layouts: [:dvorak])
+      assert "hest.com" in Enum.map(dvorak, & &1.fqdn)
+    end
+
+    test "invalid layouts raise" do
+      assert_raise ArgumentError, ~r/:layouts/, fn ->
+        DomainTwistex.Permutate.generate_permutations("test.com", layouts: [:bepo])
+      end
+
+      assert_raise ArgumentError, ~r/:layouts/, fn ->
+        DomainTwistex.Permutate.generate_permutations("test.com", layouts: [qwerty: 2])
+      end
+    end
+
+    test "the keyboard score uses the selected layouts and weights" do
+      qwerty = DomainTwistex.Utils.calculate_fuzzy_scores("test.com", "zest.com", nil, layouts: [:qwerty])
+      qwertz = DomainTwistex.Utils.calculate_fuzzy_scores("test.com", "zest.com", nil, layouts: [:qwertz])
+      weighted = DomainTwistex.Utils.calculate_fuzzy_scores("test.com", "zest.com", nil, layouts: [qwertz: 0.5])
+
+      assert qwertz.keyboard_proximity > qwerty.keyboard_proximity
+      assert_in_delta weighted.keyboard_proximity, qwertz.keyboard_proximity / 2, 1.0e-9
+    end
+
+    test "neighbours are derived from the layout grid" do
+      alias DomainTwistex.Permutate.Keyboard
+
+      assert Enum.sort(Keyboard.neighbours(:qwerty, ?e)) == Enum.sort(~c"34wrsd")
+      assert Enum.sort(Keyboard.neighbours(:azerty, ?m)) == Enum.sort(~c"lp")
+      assert ?- in Keyboard.neighbours(:dvorak, ?s)
+      assert Keyboard.neighbours(:mobile, ?1) == []
+    end
+  end
+
+  describe "phonetic" do
+    test "emits soundalike respellings" do
+      fqdns = fn domain ->
+        domain
+        |> DomainTwistex.Permutate.generate_permutations(only: ["Phonetic"])
+        |> Enum.map(& &1.fqdn)
+      end
+
+      assert "fone.com" in fqdns.("phone.com")
+      assert "kwik.com" in fqdns.("quick.com")
+      assert "rite.com" in fqdns.("right.com")
+    end
+
+    test "keeps only candidates with a matching phonetic key" do
+      for %{unicode: unicode} <- DomainTwistex.Permutate.generate_permutations("right.com", only: ["Phonetic"]) do
+        assert DomainTwistex.Permutate.Phonetic.soundalike?("right", hd(String.split(unicode, ".")))
+      end
+    end
+
+    test "classifies soundalikes" do
+      assert {:ok, matches} = DomainTwistex.classify("fone.com", "phone.com")
+      assert %{kind: "Phonetic", original: "phone", replacement: "fone"} in matches
+    end
+  end
+
+  describe "layout switch" do
+    test "retypes a Latin label on script layouts" do
+      candidates = DomainTwistex.Permutate.generate_permutations("google.com", only: ["LayoutSwitch"], layouts: [:qwerty])
+      unicodes = Enum.map(candidates, & &1.unicode)
+
+      assert "пщщпду.com" in unicodes
+      assert Enum.all?(candidates, &String.starts_with?(&1.fqdn, "xn--"))
+    end
+
+    test "retypes a Cyrillic label on a Latin layout" do
+      fqdns =
+        "привет.com"
+        |> DomainTwistex.Permutate.generate_permutations(only: ["LayoutSwitch"], layouts: [:qwerty])
+        |> Enum.map(& &1.fqdn)
+
+      assert "ghbdtn.com" in fqdns
+    end
+
+    test "classifies layout switches" do
+      assert {:ok, matches} = DomainTwistex.classify("ghbdtn.com", "привет.com", layouts: [:qwerty])
+      assert Enum.any?(matches, &match?(%{kind: "LayoutSwitch", intended_layout: :russian, active_layout: :qwerty}, &1))
+    end
+
+    test "transcodes keys by physical position" do
+      alias DomainTwistex.Permutate.Keyboard
+
+      assert Keyboard.transcode("ghbdtn", :qwerty, :russian) == {:ok, "привет"}
+      assert Keyboard.transcode("привет", :russian, :qwerty) == {:ok, "ghbdtn"}
+      assert Keyboard.transcode("zoo", :qwertz, :russian) == {:ok, "нщщ"}
+      assert Keyboard.transcode("abc", :mobile, :russian) == :error
+    end
+  end
+
+  describe "keyword dictionaries" do
+    test "generates from packs with category tags" do
+      candidates = DomainTwistex.Permutate.generate_permutations("examplebank.com", only: ["Keyword"], keywords: :banking)
+
+      assert %{edit: %{keyword: "onlinebanking", category: "credential"}} =
+               Enum.find(candidates, &(&1.fqdn == "examplebank-onlinebanking.com"))
+
+      refute Enum.any?(candidates, &(&1.fqdn == "examplebank-checkoutnow.com"))
+    end
+
+    test "supports join styles and placements" do
+      fqdns =
+        "example.com"
+        |> DomainTwistex.Permutate.generate_permutations(
+          only: ["Keyword"],
+          keywords: ["login", "customer_service"],
+          keyword_joins: [:dot, :underscore_to_hyphen],
+          keyword_placements: [:suffix]
+        )
+        |> Enum.map(& &1.fqdn)
+        |> MapSet.new()
+
+      assert fqdns ==
+               MapSet.new([
+                 "example.login.com",
+                 "example-login.com",
+                 "example.customerservice.com",
+                 "example-customer-service.com"
+               ])
+    end
+
+    test "classifies with the selected dictionary" do
+      assert :unrelated = DomainTwistex.classify("example-acme.com", "example.com")
+
+      assert {:ok, [%{kind: "Keyword", keyword: "acme", category: "brand"}]} =
+               DomainTwistex.classify("example-acme.com", "example.com", keywords: [{"acme", "brand"}], only: ["Keyword"])
+    end
+
+    test "rejects invalid options" do
+      assert_raise ArgumentError, fn ->
+        DomainTwistex.Permutate.generate_permutations("example.com", keywords: :gambling)
+      end
+
+      assert_raise ArgumentError, fn ->
+        DomainTwistex.Permutate.generate_permutations("example.com", keyword_joins: [:slash])
+      end
+    end
+  end
+
+  describe "tld typos" do
+    test "emits typos and confusables of the TLD" do
+      fqdns =
+        "example.com"
+        |> DomainTwistex.Permutate.generate_permutations(only: ["TldTypo"], layouts: [:qwerty])
+        |> Enum.map(& &1.fqdn)
+
+      for fqdn <- ~w(example.cm example.om example.ocm example.xom example.co example.corn) do
+        assert fqdn in fqdns
+      end
+
+      refute "example.com" in fqdns
+      refute "examplecom.com" in fqdns
+      assert length(fqdns) < 100
+    end
+
+    test "folds the suffix into the label" do
+      fqdns =
+        "example.co.uk"
+        |> DomainTwistex.Permutate.generate_permutations(only: ["TldTypo"])
+        |> Enum.map(& &1.fqdn)
+
+      assert "examplecouk.com" in fqdns
+      assert "example-co-uk.net" in fqdns
+      assert "example.co.uj" in fqdns
+    end
+
+    test "classifies TLD typos" do
+      assert {:ok, matches} = DomainTwistex.classify("example.orq", "example.org")
+      assert %{kind: "TldTypo", typo: :confusable, original: "org", replacement: "orq"} in matches
+
+      assert {:ok, matches} = DomainTwistex.classify("examplecom.net", "example.com")
+      assert %{kind: "TldTypo", typo: :dot, suffix: "net"} = Enum.find(matches, &(&1.kind == "TldTypo"))
+    end
+  end
+
+  describe "various" do
+    test "emits dropped dots and service prefixes" do
+      fqdns =
+        "example.com"
+        |> DomainTwistex.Permutate.generate_permutations(only: ["Various"])
+        |> Enum.map(& &1.fqdn)
+
+      for fqdn <- ~w(wwwexample.com www-example.com examplecom.com example-com.com mail-example.com mexample.com) do
+        assert fqdn in fqdns
+      end
+    end
+
+    test "runs the last subdomain label into the label" do
+      fqdns =
+        "login.example.co.uk"
+        |> DomainTwistex.Permutate.generate_permutations(only: ["Various"])
+        |> Enum.map(& &1.fqdn)
+
+      assert "loginexample.co.uk" in fqdns
+      assert "login.examplecouk.co.uk" in fqdns
+    end
+
+    test "uses configured prefixes" do
+      fqdns =
+        "example.com"
+        |> DomainTwistex.Permutate.generate_permutations(only: ["Various"], service_prefixes: ["secure"])
+        |> Enum.map(& &1.fqdn)
+
+      assert "secure-example.com" in fqdns
+      refute "wwwexample.com" in fqdns
+
+      assert_raise ArgumentError, fn ->
+        DomainTwistex.Permutate.generate_permutations("example.com", service_prefixes: "www")
+      end
+    end
+
+    test "classifies prefixed domains" do
+      assert {:ok, matches} = DomainTwistex.classify("wwwexample.com", "example.com")
+      assert %{kind: "Various", pattern: :service_prefix, replacement: "www"} = Enum.find(matches, &(&1.kind == "Various"))
+    end
+  end
+
+  describe "morphological" do
+    test "emits plural and singular forms" do
+      fqdns = fn domain ->
+        domain
+        |> DomainTwistex.Permutate.generate_permutations(only: ["Morphological"])
+        |> Enum.map(& &1.fqdn)
+      end
+
+      assert "northwinds.com" in fqdns.("northwind.com")
+      assert "northwind.com" in fqdns.("northwinds.com")
+      assert "companies.com" in fqdns.("company.com")
+      assert "box-shop.com" in fqdns.("boxes-shop.com")
+    end
+
+    test "swaps number words and digits at word boundaries" do
+      fqdns = fn domain ->
+        domain
+        |> DomainTwistex.Permutate.generate_permutations(only: ["Morphological"])
+        |> Enum.map(& &1.fqdn)
+      end
+
+      assert "123.com" in fqdns.("onetwothree.com")
+      assert "1twothree.com" in fqdns.("onetwothree.com")
+      assert "onetwothree.com" in fqdns.("123.com")
+      assert "btob.com" in fqdns.("b2b.com")
+      assert "foryou.com" in fqdns.("4you.com")
+      assert "4-you.com" in fqdns.("for-you.com")
+
+      refute Enum.any?(fqdns.("often.com"), &String.contains?(&1, "10"))
+      refute "4tune.com" in fqdns.("fortune.com")
+    end
+
+    test "classifies with the rule applied" do
+      assert {:ok, matches} = DomainTwistex.classify("northwinds.com", "northwind.com")
+      assert Enum.any?(matches, &match?(%{kind: "Morphological", rule: :plural, original: "", replacement: "s"}, &1))
+    end
+  end
+
+  describe "likelihood ranking" do
+    alias DomainTwistex.Permutate.Likelihood
+
+    test "scores candidates between 0 and 1" do
+      for candidate <- DomainTwistex.Permutate.generate_permutations("example.com", only: ["Homoglyph", "Tld", "VowelShuffle"]) do
+        assert Likelihood.score(candidate, "example.com") >= 0.0
+        assert Likelihood.score(candidate, "example.com") <= 1.0
+      end
+    end
+
+    test "ranks likely look-alikes first" do
+      candidates = DomainTwistex.Permutate.generate_permutations("example.com", only: ["Omission", "Tld", "VowelShuffle"])
+      ranked = Likelihood.rank(candidates, "example.com")
+
+      assert length(ranked) == length(candidates)
+      assert Enum.map(ranked, & &1.likelihood) == Enum.sort(Enum.map(ranked, & &1.likelihood), :desc)
+
+      score = fn fqdn -> Enum.find(ranked, &(&1.fqdn == fqdn)).likelihood end
+      assert score.("exmple.com") > score.("exumplu.com")
+      assert score.("example.net") > score.("example.museum")
+    end
+
+    test "keeps the top candidates" do
+      candidates = DomainTwistex.Permutate.generate_permutations("example.com", only: ["Omission", "Tld"])
+
+      assert [_, _, _] = Likelihood.rank(candidates, "example.com", top_n: 3)
+    end
+
+    test "rejects invalid ordering options" do
+      assert_raise ArgumentError, fn -> DomainTwistex.Twist.analyze_domain("example.com", order_by: :random) end
+      assert_raise ArgumentError, fn -> DomainTwistex.Twist.analyze_domain("example.com", top_n: 0) end
+    end
+  end
+
+  describe "label edits" do
+    alias DomainTwistex.Permutate.Generator
+
+    test "helpers locate single-character edits" do
+      assert Generator.splits("añb") == [{0, "", ?a, "ñb"}, {1, "a", ?ñ, "b"}, {2, "añ", ?b, ""}]
+      assert Generator.insertions("google", "gooogle") == [{1, ?o}, {2, ?o}, {3, ?o}]
+      assert Generator.insertions("google", "google") == []
+      assert Generator.deletions("bücher", "bcher") == [{1, ?ü}]
+      assert Generator.difference("example", "exmaple") == {2, ~c"am", ~c"ma"}
+      assert Generator.difference("example", "example") == :same
+    end
+
+    test "classification recovers the edit of every character-level candidate" do
+      domain = "bücherregaal-shoop.de"
+
+      for kind <- ~w(Insertion Replacement Transposition Subdomain Omission Repetition VowelSwap
+                     DoubleVowelInsertion Hyphenation),
+          candidate <- DomainTwistex.Permutate.generate_permutations(domain, only: [kind]) do
+        assert {:ok, matches} = DomainTwistex.classify(candidate.fqdn, domain, only: [kind])
+        assert Map.put(candidate.edit, :kind, kind) in matches, inspect(candidate)
+      end
+    end
+  end
+
+  describe "vowel shuffle" do
+    alias DomainTwistex.Permutate.Generators.VowelShuffle
+
+    defp shuffled(domain, opts \\ []) do
+      domain
+      |> DomainTwistex.Permutate.generate_permutations([only: ["VowelShuffle"]] ++ opts)
+      |> Enum.map(& &1.fqdn)
+    end
+
+    test "generates the full product under the budget" do
+      fqdns = shuffled("example.com")
+
+      assert length(fqdns) == 124
+      assert "ixampli.com" in fqdns
+      refute "example.com" in fqdns
+      assert DomainTwistex.Permutate.skipped("example.com") == %{}
+    end
+
+    test "puts in lowercase vowels only" do
+      candidates = VowelShuffle.generate({"", "ExAmple", "com"}, [])
+
+      assert length(candidates) == 124
+      refute Enum.any?(candidates, &(&1.fqdn =~ ~r/[AEIOU]/))
+      refute Enum.any?(candidates, &(&1.fqdn == "example.com"))
+      assert {:ok, [%{kind: "VowelShuffle"}]} = DomainTwistex.classify("ixampli.com", "example.com", only: ["VowelShuffle"])
+    end
+
+    test "samples the product deterministically beyond the budget" do
+      fqdns = shuffled("aeiouaeiou.com", vowel_shuffle_budget: 100)
+
+      assert length(fqdns) == 100
+      assert fqdns == shuffled("aeiouaeiou.com", vowel_shuffle_budget: 100)
+      assert fqdns == shuffled("aeiouaeiou.com", vowel_shuffle_budget: 100, vowel_shuffle_seed: 0)
+      assert fqdns != shuffled("aeiouaeiou.com", vowel_shuffle_budget: 100, vowel_shuffle_seed: 1)
+      assert shuffled("aeiouaeiou.com", vowel_shuffle_budget: 0) == []
+    end
+
+    test "reports the candidates left out" do
+      assert DomainTwistex.Permutate.skipped("aeiouaeiou.com", vowel_shuffle_budget: 100) == %{"VowelShuffle" => 15_524}
+      assert DomainTwistex.Permutate.skipped("aeiouaeiou.com") == %{"VowelShuffle" => 14_624}
+      assert DomainTwistex.Permutate.skipped("aeiouaeiou.com", vowel_shuffle: false) == %{}
+      assert DomainTwistex.Permutate.skipped("aeiouaeiou.com", except: ["VowelShuffle"]) == %{}
+    end
+
+    test "rejects invalid budgets and seeds" do
+      assert_raise ArgumentError, fn -> shuffled("example.com", vowel_shuffle_budget: -1) end
+      assert_raise ArgumentError, fn -> shuffled("example.com", vowel_shuffle_seed: "seed") end
+    end
   end
-end
\ No newline at end of file
+end
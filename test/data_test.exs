@@ -0,0 +1,60 @@
+defmodule DomainTwistex.Permutate.DataTest do
+  # Changes the application environment, so not async
+  use ExUnit.Case
+
+  import ExUnit.CaptureLog
+
+  alias DomainTwistex.Permutate
+  alias DomainTwistex.Permutate.Data
+
+  setup do
+    on_exit(fn ->
+      Application.delete_env(:domaintwistex, :tlds_path)
+      Application.delete_env(:domaintwistex, :keywords_path)
+      Data.reload()
+    end)
+  end
+
+  describe "runtime data" do
+    test "defaults to the compiled-in lists" do
+      assert "com" in Data.tlds()
+      assert MapSet.member?(Data.tld_set(), "co.uk")
+      assert "login" in Data.keywords()
+    end
+
+    @tag :tmp_dir
+    test "reads the configured files until reloaded", %{tmp_dir: tmp_dir} do
+      tlds = Path.join(tmp_dir, "tlds.txt")
+      keywords = Path.join(tmp_dir, "keywords.txt")
+      File.write!(tlds, "# test list\nexample\nCO.TEST\n")
+      File.write!(keywords, "portal\n")
+
+      Application.put_env(:domaintwistex, :tlds_path, tlds)
+      Application.put_env(:domaintwistex, :keywords_path, keywords)
+
+      assert Data.tlds() == ["example", "co.test"]
+      assert Data.keywords() == ["portal"]
+
+      fqdns =
+        "brand.com"
+        |> Permutate.generate_permutations(only: ["Tld", "Keyword"], keyword_joins: [:hyphen])
+        |> Enum.map(& &1.fqdn)
+
+      assert Enum.sort(fqdns) == ["brand-portal.com", "brand.co.test", "brand.example", "portal-brand.com"]
+
+      File.write!(tlds, "net\n")
+      assert Data.tlds() == ["example", "co.test"]
+
+      Data.reload()
+      assert Data.tlds() == ["net"]
+      assert MapSet.equal?(Data.tld_set(), MapSet.new(["net"]))
+    end
+
+    test "falls back to the compiled-in list when the file cannot be read" do
+      Application.put_env(:domaintwistex, :tlds_path, "/nonexistent/tlds.txt")
+
+      log = capture_log(fn -> assert "com" in Data.tlds() end)
+      assert log =~ "/nonexistent/tlds.txt"
+    end
+  end
+end
@@ -0,0 +1,140 @@
+defmodule DomainTwistex.DNS.FakeTest do
+  use ExUnit.Case, async: true
+
+  alias DomainTwistex.DNS
+  alias DomainTwistex.DNS.Fake
+  alias DomainTwistex.DNS.Resolver
+
+  doctest Fake
+
+  # Private addresses, so no scan connects to a web server
+  @zone %{
+    "example.com" => %{a: "10.0.0.1", ns: ["ns1.example.com", "ns2.example.com"]},
+    "www.example.com" => %{cname: "example.com"},
+    "exmple.com" => %{a: "10.0.0.2", mx: %{priority: 10, server: "mail.exmple.com"}, txt: "v=spf1 -all"},
+    "_dmarc.exmple.com" => %{txt: ["v=DMARC1; p=none"]},
+    "exampl.com" => %{a: "10.0.0.3"},
+    "*.exampl.com" => %{a: "10.0.0.3"},
+    "examle.com" => :servfail,
+    "exampe.com" => %{a: :timeout},
+    "eample.com" => %{cname: "parked.example.net"}
+  }
+
+  @resolver [resolver: {Fake, zone: @zone}]
+
+  defp omissions, do: DomainTwistex.Permutate.generate_permutations("example.com", only: ["Omission"])
+
+  describe "lookups" do
+    test "answer from the zone" do
+      assert Fake.lookup("example.com", :a, zone: @zone) == {:ok, ["10.0.0.1"]}
+      assert Fake.lookup("Example.COM.", :ns, zone: @zone) == {:ok, ["ns1.example.com", "ns2.example.com"]}
+      assert Fake.lookup("example.com", :mx, zone: @zone) == {:ok, []}
+      assert Fake.lookup("missing.example", :a, zone: @zone) == {:error, :nxdomain}
+      assert Fake.lookup("missing.example", :a, zone: @zone, default: :servfail) == {:error, :servfail}
+    end
+
+    test "follow aliases and wildcards" do
+      assert DNS.resolve_ips("www.example.com", @resolver) == {:ok, %{ips: ["10.0.0.1"], cname: "example.com"}}
+      assert DNS.resolve_ips("eample.com", @resolver) == {:error, :no_records}
+      assert Fake.lookup("deep.www.exampl.com", :a, zone: @zone) == {:ok, ["10.0.0.3"]}
+      assert DNS.has_wildcard("exampl.com", @resolver) == {:ok, true}
+      assert DNS.has_wildcard("example.com", @resolver) == {:ok, false}
+    end
+
+    test "fail as configured" do
+      assert DNS.resolve_ips("examle.com", @resolver) == {:error, :servfail}
+      assert DNS.resolve_ips("exampe.com", @resolver) == {:error, :timeout}
+      assert Fake.lookup("exampe.com", :mx, zone: @zone) == {:ok, []}
+
+      {elapsed, {:ok, _ips}} = :timer.tc(fn -> Fake.lookup("example.com", :a, zone: @zone, delay: 20) end)
+      assert elapsed >= 20_000
+    end
+  end
+
+  describe "zone files" do
+    @tag :tmp_dir
+    test "are parsed in BIND format", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "example.com.zone")
+
+      File.write!(path, """
+      $TTL 3600
+      @       IN SOA  ns1 hostmaster (
+                      2024010101 ; serial
+                      7200 3600 1209600 300 )
+              IN NS   ns1
+              IN NS   ns2.example.net.
+              IN MX   10 mail
+              IN TXT  "v=spf1 mx; -all" "; not a comment"
+      @       300 IN A 192.0.2.1
+      www     IN CNAME @
+      v6      IN AAAA 2001:DB8::1 ; comment
+      *.wild  IN A    192.0.2.9
+      """)
+
+      zone = Fake.load!(path, origin: "example.com")
+
+      assert zone["example.com"] == %{
+               ns: ["ns1.example.com", "ns2.example.net"],
+               mx: [%{priority: 10, server: "mail.example.com"}],
+               txt: ["v=spf1 mx; -all; not a comment"],
+               a: ["192.0.2.1"]
+             }
+
+      assert zone["www.example.com"] == %{cname: ["example.com"]}
+      assert zone["v6.example.com"] == %{aaaa: ["2001:db8::1"]}
+      assert Fake.lookup("host.wild.example.com", :a, zone: path, origin: "example.com") == {:ok, ["192.0.2.9"]}
+      assert Fake.lookup("host.wild.example.net", :a, zone: path, origin: "example.net") == {:ok, ["192.0.2.9"]}
+
+      session = [resolver: {Fake, zone: path, origin: "example.com"}]
+      assert [resolver: {Fake, opened}] = Resolver.with_session(session, & &1)
+      assert opened[:zone] == zone
+    end
+  end
+
+  describe "scans" do
+    test "check domains offline" do
+      assert {:ok, result} = DomainTwistex.Utils.check_domain(%{fqdn: "exmple.com", tld: "com"}, "example.com", @resolver)
+
+      assert result.ip_addresses == ["10.0.0.2"]
+      assert result.public_ips == []
+      assert result.mx_records == [%{priority: 10, server: "mail.exmple.com"}]
+      assert result.txt_records == ["v=spf1 -all"]
+      assert result.dmarc == %{"v" => "DMARC1", "p" => "none"}
+      assert result.wildcard == false
+      assert result.server_response.status == :skipped
+
+      assert DomainTwistex.Utils.check_domain(%{fqdn: "examle.com", tld: "com"}, "example.com", @resolver) ==
+               {:error, :not_resolvable}
+    end
+
+    test "analyze a domain and drop wildcard-only results" do
+      result = DomainTwistex.Twist.analyze_domain("example.com", [only: ["Omission"], whois: false] ++ @resolver)
+
+      assert result.original.resolvable
+      assert Enum.map(result.permutations, & &1.fqdn) == ["exmple.com"]
+      assert result.stats.total == length(omissions())
+      assert result.stats.resolvable == 1
+
+      top_n = [only: ["Omission"], whois: false, top_n: 2] ++ @resolver
+      assert DomainTwistex.Twist.analyze_domain("example.com", top_n).stats.total == length(omissions())
+    end
+
+    @tag :tmp_dir
+    test "are recorded and replayed", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "example.com.json")
+      {:ok, recorder} = Fake.start_recorder({Fake, zone: @zone})
+      opts = [only: ["Omission"], whois: false]
+
+      recorded = DomainTwistex.Twist.analyze_domain("example.com", [resolver: {Fake, recorder: recorder}] ++ opts)
+      :ok = Fake.save_recording(recorder, path)
+      replayed = DomainTwistex.Twist.analyze_domain("example.com", [resolver: {Fake, zone: path}] ++ opts)
+
+      assert Fake.recording(recorder)["*.exampl.com"] == %{a: ["10.0.0.3"]}
+      assert Fake.recording(recorder)["examle.com"][:a] == :servfail
+      refute Map.has_key?(Fake.recording(recorder), "xample.com")
+      assert replayed.original == recorded.original
+      assert replayed.permutations == recorded.permutations
+      assert replayed.stats == recorded.stats
+    end
+  end
+end
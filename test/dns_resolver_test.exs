@@ -0,0 +1,130 @@
+defmodule DomainTwistex.DNS.ResolverTest do
+  use ExUnit.Case, async: true
+
+  alias DomainTwistex.DNS
+  alias DomainTwistex.DNS.Message
+  alias DomainTwistex.DNS.Resolver
+
+  doctest Resolver
+  doctest Message
+  doctest Resolver.InetRes
+
+  defmodule StaticResolver do
+    @behaviour DomainTwistex.DNS.Resolver
+
+    @impl true
+    def lookup("www.example.com", :cname, _opts), do: {:ok, ["example.com"]}
+    def lookup(name, :a, _opts) when name in ["example.com", "www.example.com"], do: {:ok, ["192.0.2.1"]}
+    def lookup("example.com", :mx, opts), do: {:ok, [%{priority: 10, server: Keyword.fetch!(opts, :mx)}]}
+    def lookup("example.com", :ns, _opts), do: {:error, :servfail}
+    def lookup("_dmarc.example.com", :txt, _opts), do: {:ok, ["v=DMARC1; p=reject"]}
+    def lookup(name, _type, _opts) when name in ["example.com", "www.example.com"], do: {:ok, []}
+    def lookup(_name, _type, _opts), do: {:error, :nxdomain}
+  end
+
+  defmodule SessionResolver do
+    @behaviour DomainTwistex.DNS.Resolver
+
+    @impl true
+    def lookup(_name, :a, opts), do: {:ok, [Keyword.fetch!(opts, :session)]}
+    def lookup(_name, _type, _opts), do: {:ok, []}
+
+    @impl true
+    def open(opts), do: Keyword.put_new(opts, :session, "192.0.2.7")
+
+    @impl true
+    def close(opts) do
+      send(Keyword.fetch!(opts, :test), {:closed, opts[:session]})
+      :ok
+    end
+  end
+
+  @resolver [resolver: {StaticResolver, mx: "mail.example.com"}]
+
+  defp response(answers) do
+    header = :inet_dns.make_header(id: 0, qr: true, opcode: :query, rd: true, ra: true)
+
+    anlist =
+      for {domain, type, data} <- answers do
+        :inet_dns.make_rr(domain: String.to_charlist(domain), type: type, class: :in, ttl: 60, data: data)
+      end
+
+    :inet_dns.encode(:inet_dns.make_msg(header: header, anlist: anlist))
+  end
+
+  describe "wire format" do
+    test "encodes a recursive query" do
+      assert {:ok, msg} = :inet_dns.decode(Message.encode_query("example.com", :mx, 42))
+      assert msg |> :inet_dns.msg(:header) |> :inet_dns.header(:id) == 42
+      assert msg |> :inet_dns.msg(:header) |> :inet_dns.header(:rd) == true
+      assert [query] = :inet_dns.msg(msg, :qdlist)
+      assert :inet_dns.dns_query(query, :domain) == ~c"example.com"
+      assert :inet_dns.dns_query(query, :type) == :mx
+    end
+
+    test "decodes the answers of the type asked for" do
+      aliased = response([{"www.example.com", :cname, ~c"example.com"}, {"example.com", :a, {192, 0, 2, 1}}])
+
+      assert Message.decode_response(aliased, :a) == {:ok, ["192.0.2.1"]}
+      assert Message.decode_response(aliased, :cname) == {:ok, ["example.com"]}
+      assert Message.decode_response(aliased, :mx) == {:ok, []}
+
+      assert Message.decode_response(response([{"example.com", :mx, {10, ~c"mail.example.com"}}]), :mx) ==
+               {:ok, [%{priority: 10, server: "mail.example.com"}]}
+
+      assert Message.decode_response(response([{"example.com", :txt, [~c"v=spf1 ", ~c"-all"]}]), :txt) ==
+               {:ok, ["v=spf1 -all"]}
+
+      assert Message.decode_response(response([{"example.com", :aaaa, {0x2001, 0xDB8, 0, 0, 0, 0, 0, 1}}]), :aaaa) ==
+               {:ok, ["2001:db8::1"]}
+    end
+
+    test "reports response codes and malformed messages" do
+      assert Message.decode_response(<<0, 0, 0x81, 0x82, 0::64>>, :a) == {:error, :servfail}
+      assert Message.decode_response(<<0, 0, 0x81, 0x85, 0::64>>, :a) == {:error, :refused}
+      assert Message.decode_response(<<1, 2, 3>>, :a) == {:error, :invalid_response}
+    end
+  end
+
+  describe "resolver selection" do
+    test "rejects modules that are not resolvers" do
+      assert_raise ArgumentError, fn -> Resolver.from_opts(resolver: String) end
+      assert_raise ArgumentError, fn -> Resolver.from_opts(resolver: "9.9.9.9") end
+      assert_raise ArgumentError, fn -> DomainTwistex.Twist.analyze_domain("example.com", resolver: String) end
+    end
+
+    test "parses nameservers" do
+      assert Resolver.InetRes.nameserver("1.1.1.1:5353") == {{1, 1, 1, 1}, 5353}
+      assert Resolver.InetRes.nameserver("2620:fe::fe") == {{9760, 254, 0, 0, 0, 0, 0, 254}, 53}
+      assert Resolver.InetRes.nameserver({{9, 9, 9, 9}, 53}) == {{9, 9, 9, 9}, 53}
+      assert_raise ArgumentError, fn -> Resolver.InetRes.nameserver("dns.quad9.net") end
+    end
+
+    test "routes every DNS query through the resolver" do
+      assert DNS.resolve_ips("www.example.com", @resolver) == {:ok, %{ips: ["192.0.2.1"], cname: "example.com"}}
+      assert DNS.resolve_ips("example.com", @resolver) == {:ok, %{ips: ["192.0.2.1"], cname: nil}}
+      assert DNS.resolve_ips("missing.example", @resolver) == {:error, :no_records}
+      assert DNS.get_mx_records("example.com", @resolver) == {:ok, [%{priority: 10, server: "mail.example.com"}]}
+      assert DNS.get_mx_records("missing.example", @resolver) == {:ok, []}
+      assert DNS.get_nameservers("example.com", @resolver) == {:error, "DNS lookup failed: :servfail"}
+      assert DNS.get_txt_records("example.com", @resolver) == {:ok, []}
+      assert DNS.check_dmarc("example.com", @resolver) == {:ok, %{"v" => "DMARC1", "p" => "reject"}}
+      assert DNS.has_wildcard("example.com", @resolver) == {:ok, false}
+    end
+
+    test "open resolvers for the length of a session" do
+      opts = [resolver: {SessionResolver, test: self()}]
+
+      assert Resolver.with_session(opts, &DNS.resolve_ips("example.com", &1)) ==
+               {:ok, %{ips: ["192.0.2.7"], cname: nil}}
+
+      assert_received {:closed, "192.0.2.7"}
+
+      opened = [resolver: {SessionResolver, test: self(), session: "192.0.2.8"}]
+      assert Resolver.with_session(opened, & &1) == opened
+      refute_received {:closed, _session}
+
+      assert Resolver.with_session(@resolver, & &1) == @resolver
+    end
+  end
+end
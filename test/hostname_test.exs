@@ -0,0 +1,54 @@
+defmodule DomainTwistex.Permutate.HostnameTest do
+  use ExUnit.Case
+
+  alias DomainTwistex.Permutate.Hostname
+
+  doctest Hostname
+
+  describe "validate/1" do
+    test "accepts LDH names and valid A-labels" do
+      for name <- ["example.com", "ex-ample.co.uk", "ex--ample.com", "1password.com", "xn--a--yka.com", "xn--ggle-5qa.com"] do
+        assert Hostname.validate(name) == :ok, name
+      end
+    end
+
+    test "checks Unicode names in their ASCII form" do
+      assert Hostname.validate("gögle.com") == :ok
+      assert Hostname.validate("Gögle.com") == {:error, {:uppercase, "Gögle.com"}}
+      assert {:error, {:hyphen_start_end, _}} = Hostname.validate("gögle-.com")
+    end
+
+    test "reports the reason and label for each rejection" do
+      long_label = String.duplicate("a", 64)
+      long_name = Enum.map_join(1..4, ".", fn _ -> String.duplicate("a", 63) end) <> ".com"
+
+      assert Hostname.validate("") == {:error, {:empty, ""}}
+      assert Hostname.validate("example..com") == {:error, {:empty_label, ""}}
+      assert Hostname.validate(".example.com") == {:error, {:empty_label, ""}}
+      assert Hostname.validate("example.com.") == {:error, {:empty_label, ""}}
+      assert Hostname.validate(long_label <> ".com") == {:error, {:label_too_long, long_label}}
+      assert Hostname.validate(long_name) == {:error, {:domain_too_long, long_name}}
+      assert Hostname.validate("Example.com") == {:error, {:uppercase, "Example"}}
+      assert Hostname.validate("my_site.com") == {:error, {{:invalid_character, "_"}, "my_site"}}
+      assert Hostname.validate("example.c!m") == {:error, {{:invalid_character, "!"}, "c!m"}}
+      assert Hostname.validate("-example.com") == {:error, {:hyphen_start_end, "-example"}}
+      assert Hostname.validate("example-.com") == {:error, {:hyphen_start_end, "example-"}}
+      assert Hostname.validate("ab--cd.com") == {:error, {:hyphen_3_4, "ab--cd"}}
+      assert Hostname.validate("xn--a-b.com") == {:error, {:invalid_punycode, "xn--a-b"}}
+      assert Hostname.validate("example.123") == {:error, {:numeric_tld, "123"}}
+    end
+  end
+
+  describe "permutation filtering" do
+    test "keeps A-labels with hyphens and drops names no resolver accepts" do
+      fqdns =
+        "ab-u.com"
+        |> DomainTwistex.Permutate.generate_permutations(only: ["Homoglyph", "Hyphenation"])
+        |> Enum.map(& &1.fqdn)
+
+      assert "xn--ab--joa.com" in fqdns
+      assert Enum.all?(fqdns, &Hostname.valid?/1)
+      refute "ab--u.com" in fqdns
+    end
+  end
+end
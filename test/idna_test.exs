@@ -0,0 +1,47 @@
+defmodule DomainTwistex.Permutate.IDNATest do
+  use ExUnit.Case
+
+  alias DomainTwistex.Permutate.IDNA
+
+  describe "punycode" do
+    test "encodes and decodes RFC 3492 labels" do
+      for {unicode, encoded} <- [
+            {"bücher", "bcher-kva"},
+            {"münchen", "mnchen-3ya"},
+            {"ğöögle", "gle-rnaa8v"},
+            {"例子", "fsqu00a"},
+            {"пример", "e1afmkfd"}
+          ] do
+        assert IDNA.punycode_encode(unicode) == encoded
+        assert IDNA.punycode_decode(encoded) == {:ok, unicode}
+      end
+    end
+
+    test "rejects malformed input" do
+      assert IDNA.punycode_decode("a-b!") == :error
+      assert IDNA.punycode_decode("bcher-kvaé") == :error
+    end
+  end
+
+  describe "to_ascii/1 and to_unicode/1" do
+    test "round-trips internationalized domains" do
+      assert IDNA.to_ascii("ğöögle.com") == {:ok, "xn--gle-rnaa8v.com"}
+      assert IDNA.to_unicode("xn--gle-rnaa8v.com") == {:ok, "ğöögle.com"}
+      assert IDNA.to_ascii("пример.рф") == {:ok, "xn--e1afmkfd.xn--p1ai"}
+    end
+
+    test "maps case and compatibility characters" do
+      assert IDNA.to_ascii("Bücher.DE") == {:ok, "xn--bcher-kva.de"}
+      assert IDNA.to_ascii("ｅｘａｍｐｌｅ．com") == {:ok, "example.com"}
+    end
+
+    test "rejects labels forbidden by IDNA" do
+      assert {:error, {:hyphen_start_end, _}} = IDNA.to_ascii("-bücher.de")
+      assert {:error, {{:disallowed, "_"}, _}} = IDNA.to_ascii("bü_cher.de")
+      assert {:error, {:leading_combining_mark, _}} = IDNA.to_ascii("\u0301abc.com")
+      assert {:error, {:bidi, _}} = IDNA.to_ascii("aשלום.com")
+      assert {:error, {:invalid_punycode, _}} = IDNA.to_ascii("xn--a-b.com")
+      assert {:error, {:label_too_long, _}} = IDNA.to_ascii(String.duplicate("ü", 60) <> ".com")
+    end
+  end
+end
@@ -0,0 +1,40 @@
+defmodule DomainTwistex.Permutate.KeywordsTest do
+  use ExUnit.Case
+
+  alias DomainTwistex.Permutate.Keywords
+
+  describe "load/1" do
+    test "defaults to the bundled list with categories" do
+      keywords = Keywords.load([])
+
+      assert {"login", "credential"} in keywords
+      assert {"helpdesk", "support"} in keywords
+      assert length(keywords) > 500
+    end
+
+    test "loads named packs" do
+      assert {"connectwallet", "credential"} in Keywords.load(keywords: :crypto)
+      assert {"tracking", "shipping"} in Keywords.load(keywords: [:retail])
+    end
+
+    @tag :tmp_dir
+    test "reads files at runtime", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "keywords.txt")
+      File.write!(path, "# brand words\nAcme brand\n\nlogin\ncustomer_service support\n")
+
+      assert Keywords.load(keywords: path) ==
+               [{"acme", "brand"}, {"login", "credential"}, {"customer_service", "support"}]
+
+      File.write!(path, "portal credential\nextra\n")
+      assert Keywords.load(keywords: [{:file, path}]) == [{"portal", "credential"}, {"extra", "generic"}]
+    end
+  end
+
+  describe "validate_opts/1" do
+    test "rejects unknown packs and missing files" do
+      assert :ok = Keywords.validate_opts(keywords: [:banking, "acme", {"sso", "credential"}])
+      assert {:error, _} = Keywords.validate_opts(keywords: :gambling)
+      assert {:error, _} = Keywords.validate_opts(keywords: "/nonexistent/keywords.txt")
+    end
+  end
+end
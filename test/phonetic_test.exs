@@ -0,0 +1,29 @@
+defmodule DomainTwistex.Permutate.PhoneticTest do
+  use ExUnit.Case
+
+  alias DomainTwistex.Permutate.Phonetic
+
+  describe "double_metaphone/1" do
+    test "encodes reference words" do
+      assert Phonetic.double_metaphone("smith") == {"SM0", "XMT"}
+      assert Phonetic.double_metaphone("schmidt") == {"XMT", "SMT"}
+      assert Phonetic.double_metaphone("knight") == {"NT", "NT"}
+      assert Phonetic.double_metaphone("xavier") == {"SF", "SFR"}
+    end
+
+    test "ignores case and non-letters" do
+      assert Phonetic.double_metaphone("Phone-2") == Phonetic.double_metaphone("phone")
+      assert Phonetic.double_metaphone("") == {"", ""}
+    end
+  end
+
+  describe "soundalike?/2" do
+    test "matches respellings" do
+      assert Phonetic.soundalike?("phone", "fone")
+      assert Phonetic.soundalike?("quick", "kwik")
+      assert Phonetic.soundalike?("right", "rite")
+      refute Phonetic.soundalike?("right", "light")
+      refute Phonetic.soundalike?("", "")
+    end
+  end
+end
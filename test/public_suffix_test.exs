@@ -0,0 +1,29 @@
+defmodule DomainTwistex.Permutate.PublicSuffixTest do
+  use ExUnit.Case
+
+  alias DomainTwistex.Permutate.PublicSuffix
+
+  describe "split/1" do
+    test "splits multi-label public suffixes" do
+      assert PublicSuffix.split("login.example.co.uk") == {:ok, {"login", "example", "co.uk"}}
+      assert PublicSuffix.split("shop.brand.com") == {:ok, {"shop", "brand", "com"}}
+      assert PublicSuffix.split("Example.COM.") == {:ok, {"", "example", "com"}}
+    end
+
+    test "honours wildcard and exception rules" do
+      assert PublicSuffix.split("foo.bar.ck") == {:ok, {"", "foo", "bar.ck"}}
+      assert PublicSuffix.split("www.ck") == {:ok, {"", "www", "ck"}}
+    end
+
+    test "returns :error when there is no registrable label" do
+      assert PublicSuffix.split("co.uk") == :error
+      assert PublicSuffix.split("localhost") == :error
+    end
+  end
+
+  test "registrable_domain/1 and public_suffix/1" do
+    assert PublicSuffix.registrable_domain("a.b.example.co.uk") == "example.co.uk"
+    assert PublicSuffix.public_suffix("a.b.example.co.uk") == "co.uk"
+    assert PublicSuffix.public_suffix("example.unknownsuffix") == "unknownsuffix"
+  end
+end